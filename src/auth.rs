@@ -0,0 +1,626 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use super::*;
+
+#[derive(Clone, derive_more::Debug)]
+/// Select authentication method.
+pub enum Auth {
+    /// No authentication.
+    None,
+    /// [Basic Auth](https://developer.mozilla.org/en-US/docs/Web/HTTP/Authentication#basic_authentication_scheme).
+    Basic {
+        /// Username.
+        username: String,
+        /// Password, optional.
+        #[debug(skip)]
+        password: Option<String>,
+    },
+    /// [Digest Auth](https://www.rfc-editor.org/rfc/rfc7616) with `MD5` or
+    /// `SHA-256` and `qop=auth`, see [`Auth::digest`].
+    ///
+    /// The first request is answered by the server with a challenge and
+    /// resent, the challenge is cached for later requests.
+    Digest {
+        /// Username.
+        username: String,
+        /// Password, optional.
+        #[debug(skip)]
+        password: Option<String>,
+        /// Cached challenge, shared between clones.
+        #[debug(skip)]
+        state: DigestState,
+    },
+    /// [Bearer token](https://www.rfc-editor.org/rfc/rfc6750), e.g., an OAuth2
+    /// access token.
+    Bearer(#[debug(skip)] String),
+    /// Credentials supplied per request by a [`CredentialProvider`].
+    Provider(#[debug(skip)] Arc<dyn CredentialProvider>),
+}
+
+/// Supplies credentials for [`Auth::Provider`], e.g., OAuth2 access tokens
+/// that need to be refreshed.
+///
+/// ```
+/// # use std::sync::{Arc, Mutex};
+/// # use webdav_client::{Auth, CredentialProvider};
+/// struct OAuth2 {
+///     token: Mutex<String>,
+/// }
+///
+/// impl CredentialProvider for OAuth2 {
+///     fn authorization(&self, _method: &str, _url: &str) -> Option<String> {
+///         Some(format!("Bearer {}", self.token.lock().unwrap()))
+///     }
+///
+///     fn unauthorized(&self, _challenges: &[&str]) -> bool {
+///         *self.token.lock().unwrap() = "refreshed".to_owned();
+///         true
+///     }
+/// }
+///
+/// let auth = Auth::Provider(Arc::new(OAuth2 {
+///     token: Mutex::new("expired".to_owned()),
+/// }));
+/// ```
+pub trait CredentialProvider: Send + Sync {
+    /// Value of the `Authorization` header for a request, `None` to send the
+    /// request without it.
+    fn authorization(&self, method: &str, url: &str) -> Option<String>;
+
+    /// Called when the server responds with `401 Unauthorized`,
+    /// `challenges` are the values of the `WWW-Authenticate` headers.
+    ///
+    /// Returns whether the request should be resent, e.g., after refreshing
+    /// an expired token. This is called synchronously, also for `Async`
+    /// clients. Requests with a streamed [`Body`] cannot be resent, but the
+    /// refreshed credentials are used for the following requests.
+    fn unauthorized(&self, challenges: &[&str]) -> bool {
+        let _ = challenges;
+        false
+    }
+}
+
+impl Auth {
+    /// [`Auth::Digest`] without cached challenge.
+    #[must_use]
+    pub fn digest(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Self::Digest {
+            username: username.into(),
+            password: Some(password.into()),
+            state: DigestState::default(),
+        }
+    }
+
+    /// Adds the `Authorization` header to `request`, if credentials are
+    /// known.
+    pub(crate) fn authorize<R: Request>(&self, request: R, method: &str, url: &str) -> R {
+        match self.authorization(method, url) {
+            Some(authorization) => request.header(b"authorization", authorization),
+            None => request,
+        }
+    }
+
+    fn authorization(&self, method: &str, url: &str) -> Option<Vec<u8>> {
+        match self {
+            Auth::None => None,
+            Auth::Basic { username, password } => Some(basic_auth(username, password.as_deref())),
+            Auth::Digest {
+                username,
+                password,
+                state,
+            } => {
+                let mut challenge = state.lock();
+                let challenge = challenge.as_mut()?;
+                challenge.nc += 1;
+                Some(
+                    challenge
+                        .authorization(
+                            username,
+                            password.as_deref().unwrap_or_default(),
+                            method,
+                            request_target(url),
+                            &cnonce(),
+                        )
+                        .into_bytes(),
+                )
+            }
+            Auth::Bearer(token) => Some(format!("Bearer {token}").into_bytes()),
+            Auth::Provider(provider) => provider.authorization(method, url).map(String::into_bytes),
+        }
+    }
+
+    /// Whether requests might need to be resent after a `401 Unauthorized`
+    /// challenge.
+    pub(crate) fn may_retry(&self) -> bool {
+        matches!(self, Auth::Digest { .. } | Auth::Provider(_))
+    }
+
+    /// Whether credentials can only be sent after receiving a challenge.
+    pub(crate) fn needs_challenge(&self) -> bool {
+        matches!(self, Auth::Digest { state, .. } if state.lock().is_none())
+    }
+
+    /// Handles a `401 Unauthorized` `response`, returns whether the request
+    /// should be resent.
+    pub(crate) fn challenge(&self, response: &impl Response) -> bool {
+        if response.status() != 401 {
+            return false;
+        }
+        let challenges = response
+            .header_all("www-authenticate")
+            .into_iter()
+            .filter_map(|header| str::from_utf8(header).ok());
+        let state = match self {
+            Auth::Digest { state, .. } => state,
+            Auth::Provider(provider) => {
+                return provider.unauthorized(&challenges.collect::<Vec<_>>());
+            }
+            _ => return false,
+        };
+        let challenge = challenges
+            .flat_map(parse_challenges)
+            .filter_map(|(scheme, params)| {
+                scheme
+                    .eq_ignore_ascii_case("digest")
+                    .then(|| DigestChallenge::from_params(&params))
+                    .flatten()
+            })
+            .max_by_key(|challenge| challenge.algorithm.strength());
+        match challenge {
+            Some(challenge) => {
+                *state.lock() = Some(challenge);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+/// Challenge of [`Auth::Digest`] cached between requests.
+pub struct DigestState(Arc<Mutex<Option<DigestChallenge>>>);
+
+impl DigestState {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<DigestChallenge>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DigestAlgorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl DigestAlgorithm {
+    fn parse(value: &str) -> Option<Self> {
+        [
+            ("MD5", Self::Md5),
+            ("MD5-sess", Self::Md5Sess),
+            ("SHA-256", Self::Sha256),
+            ("SHA-256-sess", Self::Sha256Sess),
+        ]
+        .into_iter()
+        .find_map(|(name, algorithm)| name.eq_ignore_ascii_case(value).then_some(algorithm))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Md5Sess => "MD5-sess",
+            Self::Sha256 => "SHA-256",
+            Self::Sha256Sess => "SHA-256-sess",
+        }
+    }
+
+    fn strength(self) -> u8 {
+        match self {
+            Self::Md5 | Self::Md5Sess => 0,
+            Self::Sha256 | Self::Sha256Sess => 1,
+        }
+    }
+
+    fn hash(self, data: &str) -> String {
+        use sha2::Digest;
+
+        let hash = match self {
+            Self::Md5 | Self::Md5Sess => md5::Md5::digest(data).to_vec(),
+            Self::Sha256 | Self::Sha256Sess => sha2::Sha256::digest(data).to_vec(),
+        };
+        util::hex(&hash)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: DigestAlgorithm,
+    /// Whether the server supports `qop=auth`, if `false` the legacy RFC 2069
+    /// response is used.
+    qop: bool,
+    /// Number of requests sent with `nonce`.
+    nc: u32,
+}
+
+impl DigestChallenge {
+    /// `None` for unsupported challenges.
+    fn from_params(params: &[(String, String)]) -> Option<Self> {
+        let param = |name: &str| {
+            params
+                .iter()
+                .find_map(|(key, value)| (key == name).then_some(value.as_str()))
+        };
+        let qop = match param("qop") {
+            Some(qop) => {
+                if !qop.split(',').any(|qop| qop.trim() == "auth") {
+                    return None;
+                }
+                true
+            }
+            None => false,
+        };
+        Some(Self {
+            realm: param("realm")?.to_owned(),
+            nonce: param("nonce")?.to_owned(),
+            opaque: param("opaque").map(str::to_owned),
+            algorithm: param("algorithm")
+                .map_or(Some(DigestAlgorithm::Md5), DigestAlgorithm::parse)?,
+            qop,
+            nc: 0,
+        })
+    }
+
+    /// Value of the `Authorization` header.
+    fn authorization(
+        &self,
+        username: &str,
+        password: &str,
+        method: &str,
+        uri: &str,
+        cnonce: &str,
+    ) -> String {
+        let hash = |data: String| self.algorithm.hash(&data);
+        let mut ha1 = hash(format!("{username}:{}:{password}", self.realm));
+        if matches!(
+            self.algorithm,
+            DigestAlgorithm::Md5Sess | DigestAlgorithm::Sha256Sess
+        ) {
+            ha1 = hash(format!("{ha1}:{}:{cnonce}", self.nonce));
+        }
+        let ha2 = hash(format!("{method}:{uri}"));
+        let nc = format!("{:08x}", self.nc);
+        let response = if self.qop {
+            hash(format!("{ha1}:{}:{nc}:{cnonce}:auth:{ha2}", self.nonce))
+        } else {
+            hash(format!("{ha1}:{}:{ha2}", self.nonce))
+        };
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{}", uri="{}", algorithm={}, nonce="{}""#,
+            quote(username),
+            quote(&self.realm),
+            quote(uri),
+            self.algorithm.name(),
+            quote(&self.nonce),
+        );
+        if self.qop {
+            write!(header, r#", nc={nc}, cnonce="{cnonce}", qop=auth"#).unwrap();
+        }
+        write!(header, r#", response="{response}""#).unwrap();
+        if let Some(opaque) = &self.opaque {
+            write!(header, r#", opaque="{}""#, quote(opaque)).unwrap();
+        }
+        header
+    }
+}
+
+/// Parses the challenges of a `WWW-Authenticate` header into their scheme and
+/// parameters, parameter names are lower case.
+fn parse_challenges(header: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut challenges: Vec<(String, Vec<_>)> = Vec::new();
+    let mut rest = header;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            return challenges;
+        }
+        let end = rest
+            .find(|c: char| c == '=' || c == ',' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let token = &rest[..end];
+        rest = rest[end..].trim_start();
+        let Some(value) = rest.strip_prefix('=') else {
+            challenges.push((token.to_owned(), Vec::new()));
+            continue;
+        };
+        let value = value.trim_start();
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            let mut unquoted = String::new();
+            let mut chars = quoted.char_indices();
+            rest = "";
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => unquoted.extend(chars.next().map(|(_, c)| c)),
+                    '"' => {
+                        rest = &quoted[i + 1..];
+                        break;
+                    }
+                    c => unquoted.push(c),
+                }
+            }
+            unquoted
+        } else {
+            let end = value
+                .find(|c: char| c == ',' || c.is_whitespace())
+                .unwrap_or(value.len());
+            rest = &value[end..];
+            value[..end].to_owned()
+        };
+        if let Some((_, params)) = challenges.last_mut() {
+            params.push((token.to_ascii_lowercase(), value));
+        }
+    }
+}
+
+/// Path and query of `url`, used as the digest `uri`.
+fn request_target(url: &str) -> &str {
+    let target = url.split_once("://").map_or(url, |(_, rest)| {
+        rest.find('/').map_or("/", |index| &rest[index..])
+    });
+    target.split('#').next().unwrap_or(target)
+}
+
+fn quote(value: &str) -> String {
+    value.replace('\\', r"\\").replace('"', r#"\""#)
+}
+
+/// 16 random bytes from the operating system, hex-encoded.
+fn cnonce() -> String {
+    let mut bytes = [0; 16];
+    getrandom::fill(&mut bytes).expect("operating system should provide random bytes");
+    util::hex(&bytes)
+}
+
+pub(crate) fn basic_auth(username: &str, password: Option<&str>) -> Vec<u8> {
+    use std::io::Write;
+
+    use base64::prelude::BASE64_STANDARD;
+    use base64::write::EncoderWriter;
+
+    let mut buf = b"Basic ".to_vec();
+    {
+        let mut encoder = EncoderWriter::new(&mut buf, &BASE64_STANDARD);
+        let _ = write!(encoder, "{username}:");
+        if let Some(password) = password {
+            let _ = write!(encoder, "{password}");
+        }
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    // Example from RFC 7616, section 3.9.1.
+    const CHALLENGE: &str = r#"Digest
+        realm="http-auth@example.org",
+        qop="auth, auth-int",
+        algorithm=SHA-256,
+        nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+        opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS",
+      Digest
+        realm="http-auth@example.org",
+        qop="auth, auth-int",
+        algorithm=MD5,
+        nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v",
+        opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    fn challenges() -> Vec<DigestChallenge> {
+        parse_challenges(&format!("Basic realm=\"basic\", {CHALLENGE}"))
+            .into_iter()
+            .filter(|(scheme, _)| scheme == "Digest")
+            .map(|(_, params)| DigestChallenge::from_params(&params).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn digest_response() {
+        let [sha256, md5] = &mut challenges()[..] else {
+            panic!("expected two challenges");
+        };
+        for (challenge, response) in [
+            (
+                sha256,
+                "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1",
+            ),
+            (md5, "8ca523f5e9506fed4657c9700eebdbec"),
+        ] {
+            challenge.nc = 1;
+            let header = challenge.authorization(
+                "Mufasa",
+                "Circle of Life",
+                "GET",
+                "/dir/index.html",
+                CNONCE,
+            );
+            assert!(
+                header.contains(&format!(r#"response="{response}""#)),
+                "{header}"
+            );
+            assert!(header.contains(&format!(r#"nc=00000001, cnonce="{CNONCE}", qop=auth"#)));
+            assert!(header.contains(r#"opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#));
+        }
+    }
+
+    #[test]
+    fn digest_unsupported() {
+        let params = |header: &str| parse_challenges(header).remove(0).1;
+        assert!(
+            DigestChallenge::from_params(&params(r#"Digest realm="a", nonce="b", qop="auth-int""#))
+                .is_none()
+        );
+        assert!(
+            DigestChallenge::from_params(&params(
+                r#"Digest realm="a", nonce="b", algorithm=SHA-512-256"#
+            ))
+            .is_none()
+        );
+        let legacy =
+            DigestChallenge::from_params(&params(r#"Digest realm="a\"b", nonce="c""#)).unwrap();
+        assert_eq!(legacy.realm, r#"a"b"#);
+        assert_eq!(legacy.algorithm, DigestAlgorithm::Md5);
+        assert!(!legacy.qop);
+    }
+
+    #[test]
+    fn digest_uri() {
+        assert_eq!(
+            request_target("https://example.com/dav/a%20b?x=1#y"),
+            "/dav/a%20b?x=1"
+        );
+        assert_eq!(request_target("https://example.com"), "/");
+    }
+
+    #[test]
+    fn digest_cnonce() {
+        let first = cnonce();
+        assert_eq!(first.len(), 32);
+        assert!(first.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_ne!(first, cnonce());
+    }
+
+    /// Client authenticated with `auth` answering requests with `handler`.
+    fn client(
+        auth: Auth,
+        handler: impl FnMut(&crate::mock::Sent) -> MockResponse + Send + 'static,
+    ) -> Client<Mock> {
+        Client::authenticated(Mock::client(handler).web_client, auth)
+    }
+
+    #[test]
+    fn bearer() {
+        let client = client(Auth::Bearer("token".into()), |_| MockResponse::new(200));
+        client.get("https://example.com/a", None).unwrap();
+        let sent = client.web_client.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].header("authorization"), Some("Bearer token"));
+    }
+
+    /// Provider refreshing its token when challenged.
+    struct Refreshing {
+        token: Mutex<String>,
+        challenges: Mutex<Vec<String>>,
+        refresh: bool,
+    }
+
+    impl CredentialProvider for Refreshing {
+        fn authorization(&self, _method: &str, _url: &str) -> Option<String> {
+            Some(format!("Bearer {}", self.token.lock().unwrap()))
+        }
+
+        fn unauthorized(&self, challenges: &[&str]) -> bool {
+            self.challenges
+                .lock()
+                .unwrap()
+                .extend(challenges.iter().map(|&challenge| challenge.to_owned()));
+            if self.refresh {
+                *self.token.lock().unwrap() = "fresh".to_owned();
+            }
+            self.refresh
+        }
+    }
+
+    fn provider_client(refresh: bool) -> (Arc<Refreshing>, Client<Mock>) {
+        let provider = Arc::new(Refreshing {
+            token: Mutex::new("expired".to_owned()),
+            challenges: Mutex::default(),
+            refresh,
+        });
+        let client = client(Auth::Provider(provider.clone()), |sent| {
+            if sent.header("authorization") == Some("Bearer fresh") {
+                MockResponse::new(200).body("ok")
+            } else {
+                MockResponse::new(401)
+                    .header("WWW-Authenticate", r#"Bearer error="invalid_token""#)
+                    .header("WWW-Authenticate", r#"Basic realm="dav""#)
+            }
+        });
+        (provider, client)
+    }
+
+    #[test]
+    fn provider_refresh() {
+        let (provider, client) = provider_client(true);
+        let body = client
+            .put("https://example.com/a", b"data".to_vec(), None)
+            .and_then(|_| client.get("https://example.com/a", None))
+            .unwrap();
+        assert_eq!(body, b"ok");
+        assert_eq!(*provider.challenges.lock().unwrap(), [
+            r#"Bearer error="invalid_token""#,
+            r#"Basic realm="dav""#
+        ]);
+
+        // The request is resent once with the same body, later requests use
+        // the refreshed token directly.
+        let sent = client.web_client.sent();
+        let authorization: Vec<_> = sent
+            .iter()
+            .map(|sent| (sent.method.as_str(), sent.header("authorization").unwrap()))
+            .collect();
+        assert_eq!(authorization, [
+            ("PUT", "Bearer expired"),
+            ("PUT", "Bearer fresh"),
+            ("GET", "Bearer fresh")
+        ]);
+        assert_eq!(sent[1].body, b"data");
+    }
+
+    #[test]
+    fn provider_no_retry() {
+        let (provider, client) = provider_client(false);
+        assert!(matches!(
+            client.get("https://example.com/a", None),
+            Err(Error::ErrorStatus { status: 401, .. })
+        ));
+        assert_eq!(client.web_client.sent().len(), 1);
+        assert_eq!(provider.challenges.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn digest_retry() {
+        let client = client(Auth::digest("Mufasa", "Circle of Life"), |sent| {
+            if sent.header("authorization").is_some() {
+                MockResponse::new(200)
+            } else {
+                MockResponse::new(401).header("WWW-Authenticate", CHALLENGE)
+            }
+        });
+        client
+            .get("https://example.com/dir/index.html", None)
+            .unwrap();
+        client
+            .get("https://example.com/dir/index.html", None)
+            .unwrap();
+
+        let sent = client.web_client.sent();
+        assert_eq!(sent.len(), 3);
+        assert_eq!(sent[0].header("authorization"), None);
+        for (sent, nc) in [(&sent[1], "00000001"), (&sent[2], "00000002")] {
+            let authorization = sent.header("authorization").unwrap();
+            assert!(authorization.starts_with("Digest "), "{authorization}");
+            assert!(authorization.contains(r#"username="Mufasa""#));
+            assert!(authorization.contains("algorithm=SHA-256"));
+            assert!(authorization.contains(r#"uri="/dir/index.html""#));
+            assert!(authorization.contains(&format!("nc={nc}")));
+        }
+    }
+}
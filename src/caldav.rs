@@ -0,0 +1,607 @@
+//! CalDAV ([RFC 4791](https://www.rfc-editor.org/rfc/rfc4791)) on top of
+//! [`Client`].
+//!
+//! ```no_run
+//! # use time::OffsetDateTime;
+//! # use webdav_client::caldav::{CalendarQuery, TimeRange};
+//! # use webdav_client::{Blocking, Client, WebClient};
+//! # fn list(client: &Client<impl WebClient<Asyncness = Blocking>>, start: OffsetDateTime, end: OffsetDateTime) -> webdav_client::Result<()> {
+//! let principal = client.current_user_principal("")?.expect("principal");
+//! for home in client.calendar_home_set(&principal)? {
+//!     for calendar in client.calendars(&home)? {
+//!         let query = CalendarQuery::events().time_range(TimeRange::new(start, end));
+//!         for event in client.calendar_query(&calendar.url, &query)? {
+//!             println!("{} {:?}", event.url, event.etag);
+//!         }
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use std::fmt::Write;
+
+use quick_xml::escape::escape;
+use time::{OffsetDateTime, UtcOffset};
+
+use crate::url::resolve;
+use crate::webdav_types::namespace::{APPLE_ICAL, CALDAV, CALENDARSERVER, DAV};
+use crate::webdav_types::{MultiStatus, PropValue, QualifiedName, ResourceType};
+use crate::{
+    Asyncness, Client, Depth, Precondition, PropFind, Request, Response as _, Result, WebClient,
+    with_precondition,
+};
+
+const NAMESPACES: &str = r#"xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav""#;
+
+#[derive(Debug, Clone)]
+/// Calendar collection returned by [`Client::calendars`].
+pub struct Calendar {
+    /// Absolute URL of the calendar.
+    pub url: String,
+    /// `DAV:displayname`
+    pub display_name: Option<String>,
+    /// Apple iCal `calendar-color`, e.g., `#FF8800FF`.
+    pub color: Option<String>,
+    /// Calendar Server `getctag`, changes whenever an object in the calendar
+    /// changes.
+    pub ctag: Option<String>,
+    /// Component types the calendar can contain from
+    /// `supported-calendar-component-set`, e.g., `VEVENT` and `VTODO`, empty
+    /// if the server does not report them.
+    pub components: Vec<String>,
+}
+
+impl Calendar {
+    /// Whether the calendar can contain components of type `component`,
+    /// e.g., `VEVENT`.
+    #[must_use]
+    pub fn supports(&self, component: &str) -> bool {
+        self.components.is_empty()
+            || self
+                .components
+                .iter()
+                .any(|supported| supported.eq_ignore_ascii_case(component))
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Calendar object resource, i.e., an iCalendar file containing an event,
+/// to-do or journal entry.
+pub struct CalendarObject {
+    /// Absolute URL of the resource.
+    pub url: String,
+    /// ETag to pass to [`Client::put_calendar_object`] when updating it.
+    pub etag: Option<String>,
+    /// iCalendar data, [`None`] if not
+    /// [requested](CalendarQuery::calendar_data).
+    pub data: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// `time-range` filter, matching components overlapping it.
+pub struct TimeRange {
+    /// Inclusive start, unbounded if [`None`].
+    pub start: Option<OffsetDateTime>,
+    /// Exclusive end, unbounded if [`None`].
+    pub end: Option<OffsetDateTime>,
+}
+
+impl TimeRange {
+    /// Time range from `start` to `end`, use the fields directly for ranges
+    /// unbounded on one side.
+    #[must_use]
+    pub fn new(start: OffsetDateTime, end: OffsetDateTime) -> Self {
+        Self {
+            start: Some(start),
+            end: Some(end),
+        }
+    }
+
+    fn to_xml(self) -> String {
+        let mut xml = "<c:time-range".to_owned();
+        if let Some(start) = self.start {
+            write!(xml, r#" start="{}""#, utc(start)).unwrap();
+        }
+        if let Some(end) = self.end {
+            write!(xml, r#" end="{}""#, utc(end)).unwrap();
+        }
+        xml.push_str("/>");
+        xml
+    }
+}
+
+#[derive(Debug, Clone)]
+/// `calendar-query` report, see [`Client::calendar_query`].
+///
+/// ```
+/// # use time::{Duration, OffsetDateTime};
+/// # use webdav_client::caldav::{CalendarQuery, TimeRange};
+/// let now = OffsetDateTime::now_utc();
+/// let next_week =
+///     CalendarQuery::events().time_range(TimeRange::new(now, now + Duration::days(7)));
+/// ```
+pub struct CalendarQuery {
+    component: String,
+    time_range: Option<TimeRange>,
+    calendar_data: bool,
+}
+
+impl CalendarQuery {
+    /// Queries components of type `component`, e.g., `VTODO`.
+    #[must_use]
+    pub fn new(component: impl Into<String>) -> Self {
+        Self {
+            component: component.into(),
+            time_range: None,
+            calendar_data: true,
+        }
+    }
+
+    /// Queries events (`VEVENT`).
+    #[must_use]
+    pub fn events() -> Self {
+        Self::new("VEVENT")
+    }
+
+    /// Only matches components overlapping `time_range`.
+    #[must_use]
+    pub fn time_range(mut self, time_range: TimeRange) -> Self {
+        self.time_range = Some(time_range);
+        self
+    }
+
+    /// Whether to request the iCalendar data, defaults to `true`.
+    ///
+    /// Without it only the ETags are returned, e.g., to fetch only changed
+    /// objects using [`Client::calendar_multiget`].
+    #[must_use]
+    pub fn calendar_data(mut self, calendar_data: bool) -> Self {
+        self.calendar_data = calendar_data;
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let mut xml = format!(r#"<?xml version="1.0"?><c:calendar-query {NAMESPACES}>"#);
+        xml.push_str(&prop(self.calendar_data));
+        write!(
+            xml,
+            r#"<c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="{}">"#,
+            escape(&self.component)
+        )
+        .unwrap();
+        if let Some(time_range) = self.time_range {
+            xml.push_str(&time_range.to_xml());
+        }
+        xml.push_str("</c:comp-filter></c:comp-filter></c:filter></c:calendar-query>");
+        xml
+    }
+}
+
+impl<T: WebClient<Asyncness = A>, A: Asyncness> Client<T> {
+    /// Discovers the principal of the authenticated user from
+    /// `current-user-principal` ([RFC 5397](https://www.rfc-editor.org/rfc/rfc5397)),
+    /// `url` can be any resource on the server, e.g., its root.
+    pub fn current_user_principal(
+        &self,
+        url: impl AsRef<str>,
+    ) -> A::Future<Result<Option<String>>> {
+        let url = self.url(url);
+        let prop_find = PropFind::Prop(vec![QualifiedName::dav("current-user-principal")]);
+        let response = self.prop_find_with(&url, Depth::Some(0), &prop_find);
+        A::and_then(response, move |multi_status| {
+            Ok(hrefs(&multi_status, DAV, "current-user-principal")
+                .next()
+                .map(|href| resolve(&url, href)))
+        })
+    }
+
+    /// URLs of the collections containing the calendars of `principal`
+    /// from `calendar-home-set`.
+    pub fn calendar_home_set(&self, principal: impl AsRef<str>) -> A::Future<Result<Vec<String>>> {
+        let url = self.url(principal);
+        let prop_find = PropFind::Prop(vec![QualifiedName::new(CALDAV, "calendar-home-set")]);
+        let response = self.prop_find_with(&url, Depth::Some(0), &prop_find);
+        A::and_then(response, move |multi_status| {
+            Ok(hrefs(&multi_status, CALDAV, "calendar-home-set")
+                .map(|href| resolve(&url, href))
+                .collect())
+        })
+    }
+
+    /// Lists the calendars in the calendar home `url`.
+    pub fn calendars(&self, url: impl AsRef<str>) -> A::Future<Result<Vec<Calendar>>> {
+        let url = self.url(url);
+        let prop_find = PropFind::Prop(vec![
+            QualifiedName::dav("resourcetype"),
+            QualifiedName::dav("displayname"),
+            QualifiedName::new(CALENDARSERVER, "getctag"),
+            QualifiedName::new(APPLE_ICAL, "calendar-color"),
+            QualifiedName::new(CALDAV, "supported-calendar-component-set"),
+        ]);
+        let response = self.prop_find_with(&url, Depth::Some(1), &prop_find);
+        A::and_then(response, move |multi_status| calendars(&url, &multi_status))
+    }
+
+    /// Runs a `calendar-query` report on the calendar at `url`.
+    pub fn calendar_query(
+        &self,
+        url: impl AsRef<str>,
+        query: &CalendarQuery,
+    ) -> A::Future<Result<Vec<CalendarObject>>> {
+        let url = self.url(url);
+        let response = self.report(&url, Depth::Some(1), query.to_xml());
+        A::and_then(response, move |multi_status| {
+            Ok(calendar_objects(&url, multi_status))
+        })
+    }
+
+    /// Fetches the calendar objects `hrefs` from the calendar at `url` with a
+    /// `calendar-multiget` report.
+    ///
+    /// Objects that do not exist are left out.
+    pub fn calendar_multiget(
+        &self,
+        url: impl AsRef<str>,
+        hrefs: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> A::Future<Result<Vec<CalendarObject>>> {
+        let url = self.url(url);
+        let mut body = format!(r#"<?xml version="1.0"?><c:calendar-multiget {NAMESPACES}>"#);
+        body.push_str(&prop(true));
+        for href in hrefs {
+            write!(body, "<d:href>{}</d:href>", escape(href.as_ref())).unwrap();
+        }
+        body.push_str("</c:calendar-multiget>");
+        let response = self.report(&url, Depth::Some(1), body);
+        A::and_then(response, move |multi_status| {
+            Ok(calendar_objects(&url, multi_status))
+        })
+    }
+
+    /// Creates or updates the calendar object at `url` with the iCalendar
+    /// `data`, returns the new ETag, if the server sent one.
+    ///
+    /// Without `etag` the object is only created if it does not exist
+    /// (`If-None-Match: *`), otherwise it is only updated if it still has
+    /// this ETag (`If-Match`). Both fail with [`Error::PreconditionFailed`]
+    /// otherwise.
+    ///
+    /// [`Error::PreconditionFailed`]: crate::Error::PreconditionFailed
+    pub fn put_calendar_object(
+        &self,
+        url: impl AsRef<str>,
+        data: impl Into<String>,
+        etag: Option<&str>,
+    ) -> A::Future<Result<Option<String>>> {
+        let precondition = match etag {
+            Some(etag) => Precondition::new().if_match(etag),
+            None => Precondition::new().if_none_match_any(),
+        };
+        let request = self
+            .request("PUT", url.as_ref())
+            .header(b"content-type", b"text/calendar; charset=utf-8".to_vec());
+        let response = match with_precondition(request, Some(&precondition)) {
+            Ok(request) => request.send_ok(Some(data.into().into_bytes())),
+            Err(e) => return A::ready(Err(e)),
+        };
+        A::and_then(response, |response| {
+            Ok(response.header_str("etag").map(str::to_owned))
+        })
+    }
+
+    /// Deletes the calendar object at `url` if it still has the ETag `etag`.
+    pub fn delete_calendar_object(
+        &self,
+        url: impl AsRef<str>,
+        etag: &str,
+    ) -> A::Future<Result<()>> {
+        self.delete(url, Some(&Precondition::new().if_match(etag)))
+    }
+}
+
+/// `prop` element requesting the ETag and optionally the calendar data.
+fn prop(calendar_data: bool) -> String {
+    if calendar_data {
+        "<d:prop><d:getetag/><c:calendar-data/></d:prop>".to_owned()
+    } else {
+        "<d:prop><d:getetag/></d:prop>".to_owned()
+    }
+}
+
+/// Formats `time` in UTC as iCalendar date-time, e.g., `20250301T000000Z`.
+fn utc(time: OffsetDateTime) -> String {
+    let time = time.to_offset(UtcOffset::UTC);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        time.year(),
+        u8::from(time.month()),
+        time.day(),
+        time.hour(),
+        time.minute(),
+        time.second()
+    )
+}
+
+/// Text of `value` without surrounding whitespace.
+fn text(value: Option<&PropValue>) -> Option<String> {
+    match value? {
+        PropValue::Text(text) => Some(text.trim().to_owned()),
+        _ => None,
+    }
+}
+
+/// `DAV:href`s in property `{namespace}name` of the first response.
+fn hrefs<'a>(
+    multi_status: &'a MultiStatus,
+    namespace: &str,
+    name: &str,
+) -> impl Iterator<Item = &'a str> {
+    multi_status
+        .responses
+        .first()
+        .and_then(|response| response.get(namespace, name))
+        .and_then(|value| value.get(DAV, "href"))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|href| match href {
+            PropValue::Text(href) => Some(href.trim()),
+            _ => None,
+        })
+}
+
+fn calendars(url: &str, multi_status: &MultiStatus) -> Result<Vec<Calendar>> {
+    let mut calendars = Vec::new();
+    for response in &multi_status.responses {
+        if !response
+            .resource_type()?
+            .is_some_and(|types| types.contains(&ResourceType::Calendar))
+        {
+            continue;
+        }
+        calendars.push(Calendar {
+            url: resolve(url, &response.href),
+            display_name: response.display_name()?,
+            color: text(response.get(APPLE_ICAL, "calendar-color")),
+            ctag: text(response.get(CALENDARSERVER, "getctag")),
+            components: response
+                .get(CALDAV, "supported-calendar-component-set")
+                .and_then(|value| value.get(CALDAV, "comp"))
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|comp| comp.attribute("name"))
+                .map(str::to_owned)
+                .collect(),
+        });
+    }
+    Ok(calendars)
+}
+
+/// Calendar objects of a report on the calendar at `url`, skipping the
+/// calendar itself and objects not found.
+fn calendar_objects(url: &str, multi_status: MultiStatus) -> Vec<CalendarObject> {
+    multi_status
+        .responses
+        .into_iter()
+        .filter(|response| {
+            response.is_successful() && response.relative_path(url).as_deref() != Some("")
+        })
+        .map(|response| CalendarObject {
+            url: resolve(url, &response.href),
+            etag: text(response.get(DAV, "getetag")),
+            // Kept as is, iCalendar lines end with CRLF.
+            data: match response.get(CALDAV, "calendar-data") {
+                Some(PropValue::Text(data)) => Some(data.clone()),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use time::{Date, Month};
+
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    const EVENT: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nEND:VCALENDAR\r\n";
+
+    #[test]
+    fn query() {
+        let query = CalendarQuery::events()
+            .time_range(TimeRange {
+                start: Some(
+                    Date::from_calendar_date(2025, Month::March, 1)
+                        .unwrap()
+                        .with_hms(1, 30, 0)
+                        .unwrap()
+                        .assume_offset(UtcOffset::from_hms(1, 0, 0).unwrap()),
+                ),
+                end: None,
+            })
+            .calendar_data(false);
+        assert_eq!(
+            query.to_xml(),
+            r#"<?xml version="1.0"?><c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:getetag/></d:prop><c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VEVENT"><c:time-range start="20250301T003000Z"/></c:comp-filter></c:comp-filter></c:filter></c:calendar-query>"#
+        );
+    }
+
+    #[test]
+    fn parse_calendars() {
+        let multi_status: MultiStatus = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"
+                xmlns:cs="http://calendarserver.org/ns/" xmlns:x="http://apple.com/ns/ical/">
+              <d:response>
+                <d:href>/calendars/alice/</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/calendars/alice/room%201/</d:href>
+                <d:propstat>
+                  <d:prop>
+                    <d:resourcetype><d:collection/><c:calendar/></d:resourcetype>
+                    <d:displayname>Room 1</d:displayname>
+                    <cs:getctag>"42"</cs:getctag>
+                    <x:calendar-color>#FF8800FF</x:calendar-color>
+                    <c:supported-calendar-component-set>
+                      <c:comp name="VEVENT"/><c:comp name="VTODO"/>
+                    </c:supported-calendar-component-set>
+                  </d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+            </d:multistatus>"#
+            .parse()
+            .unwrap();
+        let calendars = calendars("https://example.com/calendars/alice/", &multi_status).unwrap();
+        assert_eq!(calendars.len(), 1);
+        let calendar = &calendars[0];
+        assert_eq!(
+            calendar.url,
+            "https://example.com/calendars/alice/room%201/"
+        );
+        assert_eq!(calendar.display_name.as_deref(), Some("Room 1"));
+        assert_eq!(calendar.ctag.as_deref(), Some("\"42\""));
+        assert_eq!(calendar.color.as_deref(), Some("#FF8800FF"));
+        let mut components = calendar.components.clone();
+        components.sort();
+        assert_eq!(components, ["VEVENT", "VTODO"]);
+        assert!(calendar.supports("vevent"));
+        assert!(!calendar.supports("VJOURNAL"));
+    }
+
+    #[test]
+    fn query_objects() {
+        let client = Mock::client(|_| {
+            MockResponse::new(207).body(format!(
+                r#"<?xml version="1.0"?>
+                <d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+                  <d:response>
+                    <d:href>/cal/home/</d:href>
+                    <d:propstat>
+                      <d:prop><d:getetag>"c"</d:getetag></d:prop>
+                      <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                  </d:response>
+                  <d:response>
+                    <d:href>/cal/home/a.ics</d:href>
+                    <d:propstat>
+                      <d:prop>
+                        <d:getetag>
+                          "a1"
+                        </d:getetag>
+                        <c:calendar-data>{EVENT}</c:calendar-data>
+                      </d:prop>
+                      <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                  </d:response>
+                  <d:response>
+                    <d:href>/cal/home/missing.ics</d:href>
+                    <d:status>HTTP/1.1 404 Not Found</d:status>
+                  </d:response>
+                </d:multistatus>"#
+            ))
+        });
+        let query = CalendarQuery::events();
+        let objects = client
+            .calendar_query("https://example.com/cal/home/", &query)
+            .unwrap();
+        assert_eq!(objects.len(), 1);
+        let object = &objects[0];
+        assert_eq!(object.url, "https://example.com/cal/home/a.ics");
+        assert_eq!(object.etag.as_deref(), Some("\"a1\""));
+        assert_eq!(object.data.as_deref(), Some(EVENT));
+
+        let sent = client.web_client.sent().remove(0);
+        assert_eq!(sent.method, "REPORT");
+        assert_eq!(sent.url, "https://example.com/cal/home/");
+        assert_eq!(sent.header("depth"), Some("1"));
+        assert_eq!(sent.body, query.to_xml().into_bytes());
+    }
+
+    #[test]
+    fn multiget() {
+        let client =
+            Mock::client(|_| MockResponse::new(207).body(r#"<d:multistatus xmlns:d="DAV:"/>"#));
+        let objects = client
+            .calendar_multiget("https://example.com/cal/home/", [
+                "/cal/home/a.ics",
+                "/cal/home/b&c.ics",
+            ])
+            .unwrap();
+        assert!(objects.is_empty());
+        let sent = client.web_client.sent().remove(0);
+        assert_eq!(sent.method, "REPORT");
+        assert_eq!(
+            String::from_utf8(sent.body).unwrap(),
+            r#"<?xml version="1.0"?><c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav"><d:prop><d:getetag/><c:calendar-data/></d:prop><d:href>/cal/home/a.ics</d:href><d:href>/cal/home/b&amp;c.ics</d:href></c:calendar-multiget>"#
+        );
+    }
+
+    #[test]
+    fn put_object() {
+        let client = Mock::client(|_| MockResponse::new(201).header("ETag", "\"e2\""));
+        let url = "https://example.com/cal/home/a.ics";
+        let etag = client.put_calendar_object(url, EVENT, None).unwrap();
+        assert_eq!(etag.as_deref(), Some("\"e2\""));
+        let etag = client.put_calendar_object(url, EVENT, Some("e1")).unwrap();
+        assert_eq!(etag.as_deref(), Some("\"e2\""));
+
+        let sent = client.web_client.sent();
+        assert_eq!(sent[0].method, "PUT");
+        assert_eq!(sent[0].url, url);
+        assert_eq!(
+            sent[0].header("content-type"),
+            Some("text/calendar; charset=utf-8")
+        );
+        assert_eq!(sent[0].body, EVENT.as_bytes());
+        assert_eq!(sent[0].header("if-none-match"), Some("*"));
+        assert_eq!(sent[0].header("if-match"), None);
+        assert_eq!(sent[1].header("if-match"), Some("\"e1\""));
+        assert_eq!(sent[1].header("if-none-match"), None);
+    }
+
+    #[test]
+    fn discovery() {
+        let client = Mock::client(|sent| {
+            let prop = if String::from_utf8_lossy(&sent.body).contains("current-user-principal") {
+                "<d:current-user-principal><d:href>principals/alice/</d:href></d:\
+                 current-user-principal>"
+            } else {
+                "<c:calendar-home-set>
+                  <d:href>../../calendars/alice/</d:href>
+                  \
+                 <d:href>https://other.example.com/alice/</d:href>
+                </c:calendar-home-set>"
+            };
+            MockResponse::new(207).body(format!(
+                r#"<d:multistatus xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+                  <d:response>
+                    <d:href>{}</d:href>
+                    <d:propstat>
+                      <d:prop>{prop}</d:prop>
+                      <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                  </d:response>
+                </d:multistatus>"#,
+                sent.url
+            ))
+        })
+        .with_base_url("https://example.com/dav/");
+        let principal = client.current_user_principal("").unwrap().unwrap();
+        assert_eq!(principal, "https://example.com/dav/principals/alice/");
+        assert_eq!(client.calendar_home_set(&principal).unwrap(), [
+            "https://example.com/dav/calendars/alice/",
+            "https://other.example.com/alice/"
+        ]);
+
+        let sent = client.web_client.sent();
+        assert_eq!(sent[0].method, "PROPFIND");
+        assert_eq!(sent[0].url, "https://example.com/dav/");
+        assert_eq!(sent[0].header("depth"), Some("0"));
+        assert_eq!(sent[1].url, principal);
+        assert!(String::from_utf8_lossy(&sent[1].body).contains("calendar-home-set"));
+    }
+}
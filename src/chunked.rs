@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use sha2::{Digest, Sha256};
+
+use super::*;
+
+const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+/// Maximum number of chunks of an upload.
+const MAX_CHUNKS: u64 = 10_000;
+
+#[derive(Debug, Clone)]
+/// Options for [`Client::chunked_upload`].
+///
+/// ```
+/// # use webdav_client::ChunkedUpload;
+/// let options = ChunkedUpload::new()
+///     .chunk_size(50 * 1024 * 1024)
+///     .overwrite(false);
+/// ```
+pub struct ChunkedUpload {
+    chunk_size: u64,
+    uploads_url: Option<String>,
+    id: Option<String>,
+    overwrite: bool,
+}
+
+impl Default for ChunkedUpload {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            uploads_url: None,
+            id: None,
+            overwrite: true,
+        }
+    }
+}
+
+impl ChunkedUpload {
+    /// Minimum size of all chunks but the last required by Nextcloud.
+    pub const MIN_CHUNK_SIZE: u64 = 5 * 1024 * 1024;
+
+    /// Options uploading chunks of `10 * 1024 * 1024` bytes, replacing an
+    /// existing resource.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the chunks, increased to
+    /// [`MIN_CHUNK_SIZE`](Self::MIN_CHUNK_SIZE) or if the upload would need
+    /// more than 10000 chunks.
+    #[must_use]
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(Self::MIN_CHUNK_SIZE);
+        self
+    }
+
+    /// Sets the collection of the user's uploads, e.g.,
+    /// `https://cloud.example.com/remote.php/dav/uploads/alice`, defaults to
+    /// [`default_uploads_url`](Self::default_uploads_url).
+    #[must_use]
+    pub fn uploads_url(mut self, uploads_url: impl Into<String>) -> Self {
+        self.uploads_url = Some(uploads_url.into());
+        self
+    }
+
+    /// Sets the name of the upload collection, uploads with the same `id`
+    /// continue each other.
+    ///
+    /// Defaults to a hash of destination URL and size, i.e., an interrupted
+    /// upload of the same file is continued.
+    #[must_use]
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Whether to replace an existing resource at the destination, otherwise
+    /// the upload fails with [`Error::PreconditionFailed`] after all chunks
+    /// are uploaded.
+    #[must_use]
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Derives the uploads collection from a `destination` in the files of a
+    /// user, i.e., `…/dav/files/alice/…` becomes `…/dav/uploads/alice`.
+    ///
+    /// ```
+    /// # use webdav_client::ChunkedUpload;
+    /// assert_eq!(
+    ///     ChunkedUpload::default_uploads_url(
+    ///         "https://example.com/remote.php/dav/files/alice/disk.img"
+    ///     )
+    ///     .as_deref(),
+    ///     Some("https://example.com/remote.php/dav/uploads/alice")
+    /// );
+    /// assert_eq!(
+    ///     ChunkedUpload::default_uploads_url("https://example.com/disk.img"),
+    ///     None
+    /// );
+    /// ```
+    #[must_use]
+    pub fn default_uploads_url(destination: &str) -> Option<String> {
+        let index = destination.find("/dav/files/")? + "/dav/".len();
+        let (root, files) = destination.split_at(index);
+        let user = files["files/".len()..].split('/').next()?;
+        (!user.is_empty()).then(|| format!("{root}uploads/{user}"))
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Client<T> {
+    /// Uploads `source` to `destination` in chunks, using the chunked upload
+    /// (v2) of Nextcloud and ownCloud.
+    ///
+    /// The chunks are uploaded to a collection below the
+    /// [uploads collection](ChunkedUpload::uploads_url) and assembled with a
+    /// final `MOVE`. If an upload with the same [`id`](ChunkedUpload::id) was
+    /// interrupted, the chunks already uploaded are skipped.
+    ///
+    /// Returns the new ETag of the resource, if the server sent one.
+    pub fn chunked_upload(
+        &self,
+        destination: impl AsRef<str>,
+        mut source: impl Read + Seek,
+        options: &ChunkedUpload,
+    ) -> Result<Option<String>> {
+        let destination = self.url(destination);
+        let length = source.seek(SeekFrom::End(0))?;
+        let chunk_size = options.chunk_size.max(length.div_ceil(MAX_CHUNKS));
+        let chunks = length.div_ceil(chunk_size).max(1);
+        let uploads = match &options.uploads_url {
+            Some(uploads_url) => self.url(uploads_url),
+            None => ChunkedUpload::default_uploads_url(&destination).ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("no uploads collection known for {destination}"),
+                )
+            })?,
+        };
+        let id = options.id.clone().unwrap_or_else(|| {
+            let hash = Sha256::digest(format!("{destination}\n{length}"));
+            format!("webdav-client-{}", util::hex(&hash[..16]))
+        });
+        let upload = format!(
+            "{}/{}",
+            uploads.trim_end_matches('/'),
+            url::escape_path(&id)
+        );
+
+        let uploaded = self.uploaded_chunks(&upload, &destination)?;
+        for number in uploaded.keys().filter(|number| **number > chunks) {
+            self.delete(format!("{upload}/{number:05}"), None)?;
+        }
+        for number in 1..=chunks {
+            let start = (number - 1) * chunk_size;
+            let size = chunk_size.min(length - start);
+            if uploaded.get(&number) == Some(&size) {
+                continue;
+            }
+            source.seek(SeekFrom::Start(start))?;
+            let mut chunk = Vec::new();
+            (&mut source).take(size).read_to_end(&mut chunk)?;
+            if chunk.len() as u64 != size {
+                return Err(io::Error::from(ErrorKind::UnexpectedEof).into());
+            }
+            self.request("PUT", &format!("{upload}/{number:05}"))
+                .header(b"destination", destination.clone().into_bytes())
+                .header(b"oc-total-length", length.to_string().into_bytes())
+                .send_ok(Some(chunk))?;
+        }
+
+        let response = self
+            .request("MOVE", &format!("{upload}/.file"))
+            .header(b"destination", destination.into_bytes())
+            .header(b"oc-total-length", length.to_string().into_bytes())
+            .header(
+                b"overwrite",
+                if options.overwrite { b"T" } else { b"F" }.to_vec(),
+            )
+            .send_ok(None)?;
+        Ok(response
+            .header_str("oc-etag")
+            .or_else(|| response.header_str("etag"))
+            .map(str::to_owned))
+    }
+
+    /// Lists the chunks in the collection `upload` by number and size,
+    /// creating the collection if it does not exist.
+    fn uploaded_chunks(&self, upload: &str, destination: &str) -> Result<HashMap<u64, u64>> {
+        let prop_find = PropFind::Prop(vec![QualifiedName::dav("getcontentlength")]);
+        let multi_status = match self.prop_find_with(upload, Depth::Some(1), &prop_find) {
+            Ok(multi_status) => multi_status,
+            Err(error) if error.is_not_found() => {
+                self.request("MKCOL", upload)
+                    .header(b"destination", destination.as_bytes().to_vec())
+                    .send_ok(None)?;
+                return Ok(HashMap::new());
+            }
+            Err(error) => return Err(error),
+        };
+        let mut chunks = HashMap::new();
+        for response in &multi_status.responses {
+            if let Some(number) = response
+                .relative_path(upload)
+                .and_then(|name| name.parse().ok())
+            {
+                chunks.insert(number, response.content_length()?.unwrap_or_default());
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write;
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::mock::{Mock, MockResponse, Sent};
+
+    const UPLOAD: &str = "https://example.com/remote.php/dav/uploads/alice/upload";
+    const DESTINATION: &str = "https://example.com/remote.php/dav/files/alice/disk.img";
+
+    /// Server with the chunks `uploaded` by number and size.
+    fn client(uploaded: &[(u64, u64)]) -> Client<Mock> {
+        let mut body = String::from(
+            r#"<d:multistatus xmlns:d="DAV:">
+                <d:response>
+                    <d:href>/remote.php/dav/uploads/alice/upload/</d:href>
+                    <d:propstat><d:prop/><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+                </d:response>
+                <d:response>
+                    <d:href>/remote.php/dav/uploads/alice/upload/.file</d:href>
+                    <d:propstat><d:prop/><d:status>HTTP/1.1 200 OK</d:status></d:propstat>
+                </d:response>"#,
+        );
+        for (number, size) in uploaded {
+            write!(
+                body,
+                r"<d:response>
+                    <d:href>/remote.php/dav/uploads/alice/upload/{number:05}</d:href>
+                    <d:propstat>
+                        <d:prop><d:getcontentlength>{size}</d:getcontentlength></d:prop>
+                        <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                </d:response>"
+            )
+            .unwrap();
+        }
+        body.push_str("</d:multistatus>");
+        Mock::client(move |sent| match sent.method.as_str() {
+            "PROPFIND" => MockResponse::new(207).body(body.clone()),
+            "MOVE" => MockResponse::new(201).header("oc-etag", r#""e1""#),
+            "DELETE" => MockResponse::new(204),
+            _ => MockResponse::new(201),
+        })
+    }
+
+    fn requests(sent: &[Sent]) -> Vec<(&str, &str)> {
+        sent.iter()
+            .map(|sent| (sent.method.as_str(), &sent.url[UPLOAD.len()..]))
+            .collect()
+    }
+
+    #[test]
+    fn uploaded_chunks() {
+        let client = client(&[(1, 5), (3, 7)]);
+        let chunks = client.uploaded_chunks(UPLOAD, DESTINATION).unwrap();
+        assert_eq!(chunks, HashMap::from([(1, 5), (3, 7)]));
+
+        let client = Mock::client(|sent| match sent.method.as_str() {
+            "PROPFIND" => MockResponse::new(404),
+            _ => MockResponse::new(201),
+        });
+        let chunks = client.uploaded_chunks(UPLOAD, DESTINATION).unwrap();
+        assert!(chunks.is_empty());
+        let sent = client.web_client.sent();
+        assert_eq!(sent[1].method, "MKCOL");
+        assert_eq!(sent[1].url, UPLOAD);
+        assert_eq!(sent[1].header("destination"), Some(DESTINATION));
+    }
+
+    #[test]
+    fn chunk_size() {
+        let chunk_size = |size| ChunkedUpload::new().chunk_size(size).chunk_size;
+        assert_eq!(chunk_size(0), ChunkedUpload::MIN_CHUNK_SIZE);
+        assert_eq!(chunk_size(1024), ChunkedUpload::MIN_CHUNK_SIZE);
+        assert_eq!(chunk_size(6 * 1024 * 1024), 6 * 1024 * 1024);
+    }
+
+    #[test]
+    fn resume() {
+        let size = ChunkedUpload::MIN_CHUNK_SIZE;
+        let length = 2 * size + 3;
+        let source: Vec<u8> = (0..length).map(|i| (i % 251) as u8).collect();
+        // Chunk 1 is complete, 2 was interrupted and 4 is left from a larger
+        // upload.
+        let client = client(&[(1, size), (2, 10), (4, size)]);
+        let options = ChunkedUpload::new()
+            .chunk_size(size)
+            .uploads_url("https://example.com/remote.php/dav/uploads/alice")
+            .id("upload");
+        let etag = client
+            .chunked_upload(DESTINATION, Cursor::new(&source), &options)
+            .unwrap();
+        assert_eq!(etag.as_deref(), Some(r#""e1""#));
+
+        let sent = client.web_client.sent();
+        assert_eq!(requests(&sent), [
+            ("PROPFIND", ""),
+            ("DELETE", "/00004"),
+            ("PUT", "/00002"),
+            ("PUT", "/00003"),
+            ("MOVE", "/.file"),
+        ]);
+        let size = usize::try_from(size).unwrap();
+        assert_eq!(sent[2].body, source[size..2 * size]);
+        assert_eq!(sent[3].body, source[2 * size..]);
+        for sent in &sent[2..] {
+            assert_eq!(sent.header("destination"), Some(DESTINATION));
+            assert_eq!(sent.header("oc-total-length"), Some("10485763"));
+        }
+        assert_eq!(sent[4].header("overwrite"), Some("T"));
+    }
+}
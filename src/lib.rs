@@ -5,26 +5,49 @@ use std::str;
 use derive_more::{Display, Error, From};
 
 pub mod webdav_types;
-use webdav_types::MultiStatus;
+use webdav_types::{MultiStatus, QualifiedName, Status};
+
+pub mod caldav;
 
 mod web_client;
 pub use web_client::*;
 
-#[derive(Clone, derive_more::Debug)]
-/// Select authentication method.
-pub enum Auth {
-    /// No authentication.
-    None,
-    /// [Basic Auth](https://developer.mozilla.org/en-US/docs/Web/HTTP/Authentication#basic_authentication_scheme).
-    Basic {
-        /// Username.
-        username: String,
-        /// Password, optional.
-        #[debug(skip)]
-        password: Option<String>,
-    },
-    // TODO Digest(),
-}
+mod auth;
+pub use auth::*;
+
+mod lock;
+pub use lock::*;
+
+mod precondition;
+pub use precondition::*;
+
+mod options;
+pub use options::*;
+
+mod url;
+
+mod util;
+
+mod walk;
+pub use walk::*;
+
+mod sync;
+pub use sync::*;
+
+mod range;
+pub use range::*;
+
+mod remote_file;
+pub use remote_file::*;
+
+mod chunked;
+pub use chunked::*;
+
+mod tus;
+pub use tus::*;
+
+#[cfg(test)]
+mod mock;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
 /// Select depth [`Client::prop_find`].
@@ -35,6 +58,278 @@ pub enum Depth {
     Infinity,
 }
 
+impl Depth {
+    fn to_header(self) -> Vec<u8> {
+        match self {
+            Depth::Some(n) => n.to_string().into_bytes(),
+            Depth::Infinity => b"infinity".to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Outcome of [`Client::move_to`] and [`Client::copy_to`].
+pub enum Transfer {
+    /// Destination did not exist before (`201 Created`).
+    Created,
+    /// Existing destination was overwritten (`204 No Content`).
+    Replaced,
+}
+
+#[derive(Debug, Clone)]
+/// Value of a property set through [`PropertyUpdate`].
+pub enum PropertyValue {
+    /// Text content, will be escaped.
+    Text(String),
+    /// Raw XML content, inserted as is.
+    ///
+    /// Unprefixed elements are in the namespace of the property, other
+    /// namespaces need to be declared in the XML itself.
+    Xml(String),
+}
+
+#[derive(Debug, Clone)]
+enum PropertyOperation {
+    Set {
+        namespace: String,
+        name: String,
+        value: PropertyValue,
+    },
+    Remove {
+        namespace: String,
+        name: String,
+    },
+}
+
+impl PropertyOperation {
+    fn name(&self) -> &str {
+        match self {
+            Self::Set { name, .. } | Self::Remove { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Property changes for [`Client::prop_patch`].
+///
+/// The operations are applied by the server in the order they were added.
+///
+/// ```
+/// # use webdav_client::PropertyUpdate;
+/// let update = PropertyUpdate::new()
+///     .set("http://owncloud.org/ns", "favorite", "1")
+///     .remove("http://example.com/ns", "color");
+/// ```
+pub struct PropertyUpdate {
+    operations: Vec<PropertyOperation>,
+}
+
+impl PropertyUpdate {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets property `{namespace}name` to a text `value`.
+    #[must_use]
+    pub fn set(
+        self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.set_value(namespace, name, PropertyValue::Text(value.into()))
+    }
+
+    /// Sets property `{namespace}name` to raw XML, see
+    /// [`PropertyValue::Xml`].
+    #[must_use]
+    pub fn set_xml(
+        self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        xml: impl Into<String>,
+    ) -> Self {
+        self.set_value(namespace, name, PropertyValue::Xml(xml.into()))
+    }
+
+    /// Sets property `{namespace}name` to `value`.
+    #[must_use]
+    pub fn set_value(
+        mut self,
+        namespace: impl Into<String>,
+        name: impl Into<String>,
+        value: PropertyValue,
+    ) -> Self {
+        self.operations.push(PropertyOperation::Set {
+            namespace: namespace.into(),
+            name: name.into(),
+            value,
+        });
+        self
+    }
+
+    /// Removes property `{namespace}name`.
+    #[must_use]
+    pub fn remove(mut self, namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        self.operations.push(PropertyOperation::Remove {
+            namespace: namespace.into(),
+            name: name.into(),
+        });
+        self
+    }
+
+    fn to_xml(&self) -> Result<String> {
+        use quick_xml::escape::escape;
+
+        check_names(self.operations.iter().map(PropertyOperation::name))?;
+
+        let mut body = String::new();
+        write!(
+            body,
+            r#"<?xml version="1.0"?><d:propertyupdate xmlns:d="DAV:">"#
+        )
+        .unwrap();
+        for operation in &self.operations {
+            match operation {
+                PropertyOperation::Set {
+                    namespace,
+                    name,
+                    value,
+                } => {
+                    let namespace = escape(namespace);
+                    write!(body, r#"<d:set><d:prop><{name} xmlns="{namespace}">"#).unwrap();
+                    match value {
+                        PropertyValue::Text(text) => body.push_str(&escape(text)),
+                        PropertyValue::Xml(xml) => body.push_str(xml),
+                    }
+                    write!(body, "</{name}></d:prop></d:set>").unwrap();
+                }
+                PropertyOperation::Remove { namespace, name } => {
+                    let namespace = escape(namespace);
+                    write!(
+                        body,
+                        r#"<d:remove><d:prop><{name} xmlns="{namespace}"/></d:prop></d:remove>"#
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        write!(body, "</d:propertyupdate>").unwrap();
+        Ok(body)
+    }
+}
+
+/// Fails with [`Error::InvalidName`] for the first of `names` that is not an
+/// [`NCName`](is_ncname).
+fn check_names<'a>(mut names: impl Iterator<Item = &'a str>) -> Result<()> {
+    match names.find(|name| !is_ncname(name)) {
+        Some(name) => Err(Error::InvalidName(name.to_owned())),
+        None => Ok(()),
+    }
+}
+
+/// Whether `name` is an XML [`NCName`](https://www.w3.org/TR/xml-names/#NT-NCName),
+/// i.e., can be used as element name without prefix.
+fn is_ncname(name: &str) -> bool {
+    fn is_start(c: char) -> bool {
+        matches!(c,
+            'A'..='Z'
+            | '_'
+            | 'a'..='z'
+            | '\u{C0}'..='\u{D6}'
+            | '\u{D8}'..='\u{F6}'
+            | '\u{F8}'..='\u{2FF}'
+            | '\u{370}'..='\u{37D}'
+            | '\u{37F}'..='\u{1FFF}'
+            | '\u{200C}'..='\u{200D}'
+            | '\u{2070}'..='\u{218F}'
+            | '\u{2C00}'..='\u{2FEF}'
+            | '\u{3001}'..='\u{D7FF}'
+            | '\u{F900}'..='\u{FDCF}'
+            | '\u{FDF0}'..='\u{FFFD}'
+            | '\u{10000}'..='\u{EFFFF}'
+        )
+    }
+    let mut chars = name.chars();
+    chars.next().is_some_and(is_start) && chars.all(|c| {
+        is_start(c)
+            || matches!(c,
+                '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}'
+            )
+    })
+}
+
+#[derive(Debug, Clone)]
+/// Status of a single property returned by [`Client::prop_patch`].
+pub struct PropertyStatus {
+    /// Name of the property.
+    pub name: QualifiedName,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone)]
+/// Body of a `PROPFIND` request, see [`Client::prop_find_with`].
+pub enum PropFind {
+    /// Request the values of specific properties.
+    Prop(Vec<QualifiedName>),
+    /// Request the values of all dead properties and the live properties
+    /// defined by RFC 4918, plus the `include`d properties.
+    AllProp {
+        /// Additional properties, e.g., expensive live properties that are
+        /// not returned for `allprop`.
+        include: Vec<QualifiedName>,
+    },
+    /// Request the names of all properties, see [`Client::prop_names`].
+    PropName,
+}
+
+impl PropFind {
+    fn to_xml(&self) -> Result<String> {
+        fn write_names(body: &mut String, names: &[QualifiedName]) {
+            for QualifiedName { namespace, name } in names {
+                let namespace = quick_xml::escape::escape(namespace);
+                write!(body, r#"<{name} xmlns="{namespace}"/>"#).unwrap();
+            }
+        }
+
+        match self {
+            PropFind::Prop(names) | PropFind::AllProp { include: names } => {
+                check_names(names.iter().map(|name| name.name.as_str()))?;
+            }
+            PropFind::PropName => {}
+        }
+
+        let mut body = String::new();
+        write!(body, r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:">"#).unwrap();
+        match self {
+            PropFind::Prop(names) => {
+                write!(body, "<d:prop>").unwrap();
+                write_names(&mut body, names);
+                write!(body, "</d:prop>").unwrap();
+            }
+            PropFind::AllProp { include } => {
+                write!(body, "<d:allprop/>").unwrap();
+                if !include.is_empty() {
+                    write!(body, "<d:include>").unwrap();
+                    write_names(&mut body, include);
+                    write!(body, "</d:include>").unwrap();
+                }
+            }
+            PropFind::PropName => write!(body, "<d:propname/>").unwrap(),
+        }
+        write!(body, "</d:propfind>").unwrap();
+        Ok(body)
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Property names of a resource returned by [`Client::prop_names`].
+pub struct PropNames {
+    pub href: String,
+    pub names: Vec<QualifiedName>,
+}
+
 #[derive(Display, Debug, Error, From)]
 /// Error returned by [`Client`].
 pub enum Error {
@@ -44,8 +339,39 @@ pub enum Error {
     WebRequest(Box<dyn std::error::Error + Send + Sync>),
     /// Error caused in parsing the response.
     Parsing(quick_xml::DeError),
+    /// Malformed property value in the response.
+    Property(webdav_types::PropertyError),
+    /// Error accessing local files.
+    Io(std::io::Error),
     #[display("Non 2xx status code {status} {}", text.as_deref().unwrap_or_default())]
     ErrorStatus { status: u16, text: Option<String> },
+    /// Server responded with `412 Precondition Failed`, i.e., a
+    /// [`Precondition`] was not met.
+    #[display("Precondition failed {}", text.as_deref().unwrap_or_default())]
+    #[from(skip)]
+    PreconditionFailed { text: Option<String> },
+    /// Server responded with `207 Multi-Status`, i.e., the operation failed
+    /// for some of the affected resources.
+    #[display(
+        "Operation failed for {} resources",
+        _0.responses.iter().filter(|r| !r.is_successful()).count()
+    )]
+    #[from(skip)]
+    MultiStatus(#[error(not(source))] MultiStatus),
+    /// Server response violates the protocol, e.g., a `206 Partial Content`
+    /// without `Content-Range`.
+    #[display("Invalid response: {_0}")]
+    #[from(skip)]
+    InvalidResponse(#[error(not(source))] String),
+    /// Property name is not a valid XML name, see [`PropertyUpdate`] and
+    /// [`PropFind`].
+    #[display("Invalid property name `{_0}`")]
+    #[from(skip)]
+    InvalidName(#[error(not(source))] String),
+    /// [`Precondition`] with both tagged and untagged lists, which an `If`
+    /// header cannot mix.
+    #[display("`If` header cannot mix tagged and untagged lists")]
+    MixedConditions,
 }
 
 impl Error {
@@ -63,32 +389,29 @@ impl Error {
     pub fn is_conflict(&self) -> bool {
         matches!(self, Self::ErrorStatus { status: 409, .. })
     }
+
+    #[must_use]
+    pub fn is_precondition_failed(&self) -> bool {
+        matches!(self, Self::PreconditionFailed { .. })
+    }
 }
 
 /// Result returned by [`Client`].
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-fn basic_auth(username: &str, password: Option<&str>) -> Vec<u8> {
-    use std::io::Write;
-
-    use base64::prelude::BASE64_STANDARD;
-    use base64::write::EncoderWriter;
-
-    let mut buf = b"Basic ".to_vec();
-    {
-        let mut encoder = EncoderWriter::new(&mut buf, &BASE64_STANDARD);
-        let _ = write!(encoder, "{username}:");
-        if let Some(password) = password {
-            let _ = write!(encoder, "{password}");
-        }
+fn with_precondition<R: Request>(request: R, precondition: Option<&Precondition>) -> Result<R> {
+    match precondition {
+        Some(precondition) => precondition.apply(request),
+        None => Ok(request),
     }
-    buf
 }
 
 #[derive(Clone, Debug)]
 pub struct Client<T> {
     pub web_client: T,
     pub authentication: Auth,
+    /// URL relative paths are resolved against, see [`Client::url`].
+    pub base_url: Option<String>,
 }
 
 impl<T> Client<T> {
@@ -96,6 +419,7 @@ impl<T> Client<T> {
         Self {
             web_client,
             authentication: Auth::None,
+            base_url: None,
         }
     }
 
@@ -103,6 +427,7 @@ impl<T> Client<T> {
         Self {
             web_client,
             authentication,
+            base_url: None,
         }
     }
 }
@@ -125,33 +450,523 @@ impl<T: WebClient<Asyncness = A>, A: Asyncness> Client<T> {
             write!(body, "<{name}/>").unwrap();
         }
         write!(body, "</d:prop></d:propfind>").unwrap();
-        // todo!()
+        self.send_prop_find(url.as_ref(), depth, body)
+    }
+
+    /// Like [`prop_find`](Self::prop_find), but supports all kinds of
+    /// `PROPFIND` requests.
+    ///
+    /// Fails with [`Error::InvalidName`] without sending a request if a
+    /// property name is not a valid XML name.
+    pub fn prop_find_with(
+        &self,
+        url: impl AsRef<str>,
+        depth: Depth,
+        prop_find: &PropFind,
+    ) -> A::Future<Result<MultiStatus>> {
+        match prop_find.to_xml() {
+            Ok(body) => self.send_prop_find(url.as_ref(), depth, body),
+            Err(e) => A::ready(Err(e)),
+        }
+    }
+
+    /// Discovers the names of all properties (including dead properties) of
+    /// the resources at `url`.
+    pub fn prop_names(
+        &self,
+        url: impl AsRef<str>,
+        depth: Depth,
+    ) -> A::Future<Result<Vec<PropNames>>> {
+        A::and_then(
+            self.prop_find_with(url, depth, &PropFind::PropName),
+            |multi_status| {
+                Ok(multi_status
+                    .into_iter()
+                    .map(|response| {
+                        let mut names: Vec<_> = response
+                            .propstat
+                            .into_iter()
+                            .filter(|propstat| propstat.status.is_successful())
+                            .flat_map(|propstat| propstat.prop.into_keys())
+                            .collect();
+                        names.sort();
+                        PropNames {
+                            href: response.href,
+                            names,
+                        }
+                    })
+                    .collect())
+            },
+        )
+    }
+
+    /// Sends a `REPORT` request with the XML `body` to `url`, e.g., a CalDAV
+    /// `calendar-query`, see [`caldav`].
+    ///
+    /// Only reports answered with a `207 Multi-Status` are supported.
+    pub fn report(
+        &self,
+        url: impl AsRef<str>,
+        depth: Depth,
+        body: impl Into<String>,
+    ) -> A::Future<Result<MultiStatus>> {
+        self.send_multi_status("REPORT", url.as_ref(), depth, body.into())
+    }
+
+    fn send_prop_find(
+        &self,
+        url: &str,
+        depth: Depth,
+        body: String,
+    ) -> A::Future<Result<MultiStatus>> {
+        self.send_multi_status("PROPFIND", url, depth, body)
+    }
+
+    fn send_multi_status(
+        &self,
+        method: &str,
+        url: &str,
+        depth: Depth,
+        body: String,
+    ) -> A::Future<Result<MultiStatus>> {
         let response = self
-            .request("PROPFIND", url.as_ref())
-            .header(b"depth", match depth {
-                Depth::Some(n) => n.to_string().into_bytes(),
-                Depth::Infinity => b"infinity".to_vec(),
-            })
+            .request(method, url)
+            .header(b"depth", depth.to_header())
             .send_ok(Some(body.into_bytes()));
         let response = A::flat_and_then(response, Response::text);
+        A::and_then(response, |s| s.parse().map_err(Error::Parsing))
+    }
+
+    /// Sets and removes properties of the resource at `url`.
+    ///
+    /// Returns the status of every property, if any of the operations fails,
+    /// the server rejects all of them.
+    ///
+    /// A [lock token](Precondition::lock_token) is required if the resource
+    /// is locked.
+    ///
+    /// Fails with [`Error::InvalidName`] without sending a request if a
+    /// property name is not a valid XML name.
+    pub fn prop_patch(
+        &self,
+        url: impl AsRef<str>,
+        update: &PropertyUpdate,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Vec<PropertyStatus>>> {
+        let body = match update.to_xml() {
+            Ok(body) => body,
+            Err(e) => return A::ready(Err(e)),
+        };
+        let request = match with_precondition(self.request("PROPPATCH", url.as_ref()), precondition)
+        {
+            Ok(request) => request,
+            Err(e) => return A::ready(Err(e)),
+        };
+        let response = request.send_ok(Some(body.into_bytes()));
+        let response = A::flat_and_then(response, Response::text);
         A::and_then(response, |s| {
-            quick_xml::de::from_str(&s).map_err(Error::Parsing)
+            let multi_status: MultiStatus = s.parse().map_err(Error::Parsing)?;
+            Ok(multi_status
+                .into_iter()
+                .flat_map(|response| response.propstat)
+                .flat_map(|propstat| {
+                    propstat.prop.into_keys().map(move |name| PropertyStatus {
+                        name,
+                        status: propstat.status.clone(),
+                    })
+                })
+                .collect())
         })
     }
 
-    pub fn get(&self, url: impl AsRef<str>) -> A::Future<Result<Vec<u8>>> {
-        A::flat_and_then(self.get_raw(url), web_client::Response::bytes)
+    pub fn get(
+        &self,
+        url: impl AsRef<str>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Vec<u8>>> {
+        A::flat_and_then(self.get_raw(url, precondition), web_client::Response::bytes)
     }
 
-    pub fn get_raw(&self, url: impl AsRef<str>) -> A::Future<Result<T::Response>> {
-        self.request("GET", url.as_ref()).send_ok(None)
+    /// Downloads the resource at `url` into `writer` without buffering it in
+    /// memory, returns the number of bytes written.
+    pub fn download_to(
+        &self,
+        url: impl AsRef<str>,
+        writer: impl WriteBody<A>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<u64>> {
+        writer.write_body(self.get_raw(url, precondition))
     }
 
-    // pub fn put(&self, url: impl AsRef<str>, data: Vec<u8>) -> <T::Request as
-    // Request>::Result<()> {     self.request("GET", url.as_ref()).send(None);
-    // }
+    pub fn get_raw(
+        &self,
+        url: impl AsRef<str>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<T::Response>> {
+        match with_precondition(self.request("GET", url.as_ref()), precondition) {
+            Ok(request) => request.send_ok(None),
+            Err(e) => A::ready(Err(e)),
+        }
+    }
 
-    pub fn put_raw(&self, url: impl AsRef<str>) -> T::Request {
+    /// Uploads `body` to `url`, replacing the content of an existing
+    /// resource.
+    ///
+    /// `body` can either be held in memory (`Vec<u8>`) or a [`Body::Stream`]
+    /// to upload large files without buffering them, except with
+    /// [`Minreq`](crate::Minreq), which reads streams into memory.
+    ///
+    /// Returns the new ETag of the resource, if the server sent one.
+    ///
+    /// Use [`Precondition::if_match`] to avoid overwriting changes made by
+    /// others and [`Precondition::if_none_match_any`] to only create new
+    /// resources.
+    pub fn put(
+        &self,
+        url: impl AsRef<str>,
+        body: impl Into<Body<A>>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Option<String>>> {
+        let response = match with_precondition(self.request("PUT", url.as_ref()), precondition) {
+            Ok(request) => request.send_body_ok(body.into()),
+            Err(e) => return A::ready(Err(e)),
+        };
+        A::and_then(response, |response| {
+            Ok(response.header_str("etag").map(str::to_owned))
+        })
+    }
+
+    pub fn put_raw(&self, url: impl AsRef<str>) -> <Self as WebClient>::Request {
         self.request("PUT", url.as_ref())
     }
+
+    /// Deletes the resource at `url`, collections are deleted recursively.
+    ///
+    /// Returns [`Error::MultiStatus`] if some members of a collection could
+    /// not be deleted.
+    ///
+    /// A [lock token](Precondition::lock_token) is required if the resource
+    /// or its parent is locked.
+    pub fn delete(
+        &self,
+        url: impl AsRef<str>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<()>> {
+        let response = match with_precondition(self.request("DELETE", url.as_ref()), precondition) {
+            Ok(request) => request.send_ok(None),
+            Err(e) => return A::ready(Err(e)),
+        };
+        let response = A::flat_and_then(response, Response::error_on_multi_status);
+        A::and_then(response, |_| Ok(()))
+    }
+
+    /// Creates a new collection at `url`.
+    ///
+    /// The parent collection needs to exist, otherwise the server responds
+    /// with `409 Conflict`.
+    ///
+    /// A [lock token](Precondition::lock_token) is required if the parent
+    /// collection is locked.
+    pub fn mkcol(
+        &self,
+        url: impl AsRef<str>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<()>> {
+        let response = match with_precondition(self.request("MKCOL", url.as_ref()), precondition) {
+            Ok(request) => request.send_ok(None),
+            Err(e) => return A::ready(Err(e)),
+        };
+        A::and_then(response, |_| Ok(()))
+    }
+
+    /// Moves the resource at `url` to `destination`, which needs to be an
+    /// absolute URL.
+    ///
+    /// If `overwrite` is `false` and `destination` exists, the server
+    /// responds with `412 Precondition Failed`.
+    ///
+    /// A [lock token](Precondition::lock_token) is required if the source or
+    /// destination is locked.
+    pub fn move_to(
+        &self,
+        url: impl AsRef<str>,
+        destination: impl AsRef<str>,
+        overwrite: bool,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Transfer>> {
+        self.transfer(
+            "MOVE",
+            url.as_ref(),
+            destination.as_ref(),
+            overwrite,
+            None,
+            precondition,
+        )
+    }
+
+    /// Copies the resource at `url` to `destination`, which needs to be an
+    /// absolute URL.
+    ///
+    /// For collections, `depth` can either be [`Depth::Infinity`] to copy
+    /// all members or `Depth::Some(0)` to only copy the collection and its
+    /// properties.
+    ///
+    /// If `overwrite` is `false` and `destination` exists, the server
+    /// responds with `412 Precondition Failed`.
+    ///
+    /// A [lock token](Precondition::lock_token) is required if the
+    /// destination is locked.
+    pub fn copy_to(
+        &self,
+        url: impl AsRef<str>,
+        destination: impl AsRef<str>,
+        depth: Depth,
+        overwrite: bool,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Transfer>> {
+        self.transfer(
+            "COPY",
+            url.as_ref(),
+            destination.as_ref(),
+            overwrite,
+            Some(depth),
+            precondition,
+        )
+    }
+
+    fn transfer(
+        &self,
+        method: &str,
+        url: &str,
+        destination: &str,
+        overwrite: bool,
+        depth: Option<Depth>,
+        precondition: Option<&Precondition>,
+    ) -> A::Future<Result<Transfer>> {
+        let mut request = match with_precondition(self.request(method, url), precondition) {
+            Ok(request) => request,
+            Err(e) => return A::ready(Err(e)),
+        }
+        .header(b"destination", self.url(destination).into_bytes())
+        .header(b"overwrite", if overwrite { b"T" } else { b"F" }.to_vec());
+        if let Some(depth) = depth {
+            request = request.header(b"depth", depth.to_header());
+        }
+        let response = request.send_ok(None);
+        let response = A::flat_and_then(response, Response::error_on_multi_status);
+        A::and_then(response, |response| {
+            Ok(if response.status() == 204 {
+                Transfer::Replaced
+            } else {
+                Transfer::Created
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    #[test]
+    fn property_update() {
+        let update = PropertyUpdate::new()
+            .set("http://owncloud.org/ns", "favorite", "1 < 2 & \"3\"")
+            .set_xml(
+                "urn:x\"><evil",
+                "tags",
+                "<tag>a</tag><b:c xmlns:b=\"urn:b\"/>",
+            )
+            .remove("DAV:", "displayname");
+        assert_eq!(
+            update.to_xml().unwrap(),
+            r#"<?xml version="1.0"?><d:propertyupdate xmlns:d="DAV:"><d:set><d:prop><favorite xmlns="http://owncloud.org/ns">1 &lt; 2 &amp; &quot;3&quot;</favorite></d:prop></d:set><d:set><d:prop><tags xmlns="urn:x&quot;&gt;&lt;evil"><tag>a</tag><b:c xmlns:b="urn:b"/></tags></d:prop></d:set><d:remove><d:prop><displayname xmlns="DAV:"/></d:prop></d:remove></d:propertyupdate>"#
+        );
+        // Round trips through an XML parser.
+        let xml = update.to_xml().unwrap();
+        let mut reader = quick_xml::Reader::from_str(&xml);
+        while !matches!(reader.read_event().unwrap(), quick_xml::events::Event::Eof) {}
+    }
+
+    #[test]
+    fn property_names() {
+        for name in ["a", "_a-1.b", "änderung", "x\u{B7}y"] {
+            assert!(is_ncname(name), "{name}");
+        }
+        for name in ["", "1a", "-a", ".a", "a b", "a:b", "a>", "a/", "a\"", "<a"] {
+            assert!(!is_ncname(name), "{name}");
+            let update = PropertyUpdate::new()
+                .set("urn:x", "ok", "")
+                .remove("urn:x", name);
+            assert!(matches!(update.to_xml(), Err(Error::InvalidName(n)) if n == name));
+        }
+
+        let client = Mock::client(|_| MockResponse::new(207));
+        let update = PropertyUpdate::new().set("urn:x", "a b", "");
+        assert!(matches!(
+            client.prop_patch("http://example.com/file", &update, None),
+            Err(Error::InvalidName(_))
+        ));
+        assert!(client.web_client.sent().is_empty());
+    }
+
+    /// `207 Multi-Status` with a member that could not be changed.
+    const LOCKED: &str = r#"<d:multistatus xmlns:d="DAV:">
+        <d:response>
+            <d:href>/dav/dir/locked</d:href>
+            <d:status>HTTP/1.1 423 Locked</d:status>
+        </d:response>
+    </d:multistatus>"#;
+
+    #[test]
+    fn delete() {
+        let client = Mock::client(|sent| match sent.url.as_str() {
+            "https://example.com/dav/file" => MockResponse::new(204),
+            "https://example.com/dav/dir" => MockResponse::new(207).body(LOCKED),
+            _ => MockResponse::new(404),
+        })
+        .with_base_url("https://example.com/dav/");
+        client
+            .delete("file", Some(&Precondition::new().if_match("1")))
+            .unwrap();
+        let sent = &client.web_client.sent()[0];
+        assert_eq!(sent.method, "DELETE");
+        assert_eq!(sent.header("if-match"), Some(r#""1""#));
+        let Err(Error::MultiStatus(multi_status)) = client.delete("dir", None) else {
+            panic!("expected `Error::MultiStatus`");
+        };
+        assert_eq!(multi_status.responses[0].href, "/dav/dir/locked");
+        assert!(client.delete("missing", None).unwrap_err().is_not_found());
+    }
+
+    #[test]
+    fn mkcol() {
+        let client = Mock::client(|sent| match sent.url.as_str() {
+            "https://example.com/new" => MockResponse::new(201),
+            _ => MockResponse::new(405),
+        });
+        client.mkcol("https://example.com/new", None).unwrap();
+        assert_eq!(client.web_client.sent()[0].method, "MKCOL");
+        assert!(matches!(
+            client.mkcol("https://example.com/existing", None),
+            Err(Error::ErrorStatus { status: 405, .. })
+        ));
+    }
+
+    #[test]
+    fn transfer() {
+        let client = Mock::client(|sent| match sent.header("destination") {
+            Some("https://example.com/dav/new") => MockResponse::new(201),
+            Some("https://example.com/dav/existing") => MockResponse::new(204),
+            _ => MockResponse::new(207).body(LOCKED),
+        })
+        .with_base_url("https://example.com/dav/");
+        assert_eq!(
+            client.move_to("a", "new", false, None).unwrap(),
+            Transfer::Created
+        );
+        assert_eq!(
+            client
+                .copy_to("a", "existing", Depth::Some(0), true, None)
+                .unwrap(),
+            Transfer::Replaced
+        );
+        assert!(matches!(
+            client.copy_to("dir", "other", Depth::Infinity, true, None),
+            Err(Error::MultiStatus(_))
+        ));
+
+        let sent = client.web_client.sent();
+        let sent: Vec<_> = sent
+            .iter()
+            .map(|sent| {
+                (
+                    sent.method.as_str(),
+                    sent.url.as_str(),
+                    sent.header("overwrite"),
+                    sent.header("depth"),
+                )
+            })
+            .collect();
+        assert_eq!(sent, [
+            ("MOVE", "https://example.com/dav/a", Some("F"), None),
+            ("COPY", "https://example.com/dav/a", Some("T"), Some("0")),
+            (
+                "COPY",
+                "https://example.com/dav/dir",
+                Some("T"),
+                Some("infinity")
+            ),
+        ]);
+    }
+
+    #[test]
+    fn prop_find() {
+        let names = vec![
+            QualifiedName::dav("getetag"),
+            QualifiedName::new("urn:x\"><evil", "color"),
+        ];
+        assert_eq!(
+            PropFind::Prop(names.clone()).to_xml().unwrap(),
+            r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:prop><getetag xmlns="DAV:"/><color xmlns="urn:x&quot;&gt;&lt;evil"/></d:prop></d:propfind>"#
+        );
+        assert_eq!(
+            PropFind::AllProp { include: names }.to_xml().unwrap(),
+            r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:allprop/><d:include><getetag xmlns="DAV:"/><color xmlns="urn:x&quot;&gt;&lt;evil"/></d:include></d:propfind>"#
+        );
+        assert_eq!(
+            PropFind::AllProp { include: vec![] }.to_xml().unwrap(),
+            r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:allprop/></d:propfind>"#
+        );
+        assert_eq!(
+            PropFind::PropName.to_xml().unwrap(),
+            r#"<?xml version="1.0"?><d:propfind xmlns:d="DAV:"><d:propname/></d:propfind>"#
+        );
+
+        let invalid = PropFind::AllProp {
+            include: vec![QualifiedName::new("urn:x", "a/><evil")],
+        };
+        assert!(matches!(invalid.to_xml(), Err(Error::InvalidName(name)) if name == "a/><evil"));
+        let client = Mock::client(|_| MockResponse::new(207));
+        assert!(matches!(
+            client.prop_find_with("http://example.com/", Depth::Some(0), &invalid),
+            Err(Error::InvalidName(_))
+        ));
+        assert!(client.web_client.sent().is_empty());
+    }
+
+    #[test]
+    fn prop_names() {
+        let client = Mock::client(|_| {
+            MockResponse::new(207).body(
+                r#"<d:multistatus xmlns:d="DAV:" xmlns:x="urn:x">
+                    <d:response>
+                        <d:href>/dav/file</d:href>
+                        <d:propstat>
+                            <d:prop><x:color/><d:getetag/></d:prop>
+                            <d:status>HTTP/1.1 200 OK</d:status>
+                        </d:propstat>
+                        <d:propstat>
+                            <d:prop><x:secret/></d:prop>
+                            <d:status>HTTP/1.1 403 Forbidden</d:status>
+                        </d:propstat>
+                    </d:response>
+                </d:multistatus>"#,
+            )
+        });
+        let names = client
+            .prop_names("http://example.com/dav/file", Depth::Some(0))
+            .unwrap();
+        assert_eq!(names.len(), 1);
+        assert_eq!(names[0].href, "/dav/file");
+        assert_eq!(names[0].names, [
+            QualifiedName::dav("getetag"),
+            QualifiedName::new("urn:x", "color"),
+        ]);
+        let sent = &client.web_client.sent()[0];
+        assert_eq!(sent.method, "PROPFIND");
+        assert_eq!(sent.header("depth"), Some("0"));
+        assert_eq!(sent.body, PropFind::PropName.to_xml().unwrap().into_bytes());
+    }
 }
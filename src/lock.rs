@@ -0,0 +1,528 @@
+#[cfg(feature = "async")]
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use webdav_types::{Lock, LockDiscovery, LockScope, LockTimeout};
+
+use super::*;
+
+#[derive(Debug, Clone)]
+/// Parameters of a new write lock, see [`Client::lock`].
+///
+/// ```
+/// # use webdav_client::LockInfo;
+/// # use webdav_client::webdav_types::{LockScope, LockTimeout};
+/// let info = LockInfo::new(LockScope::Exclusive)
+///     .timeout(LockTimeout::Seconds(600))
+///     .owner_xml("<d:href>mailto:alice@example.com</d:href>");
+/// ```
+pub struct LockInfo {
+    scope: LockScope,
+    depth: Depth,
+    timeout: Option<LockTimeout>,
+    owner: Option<PropertyValue>,
+}
+
+impl LockInfo {
+    /// Lock with `scope` and [`Depth::Infinity`], without owner and with
+    /// the server's default timeout.
+    #[must_use]
+    pub fn new(scope: LockScope) -> Self {
+        Self {
+            scope,
+            depth: Depth::Infinity,
+            timeout: None,
+            owner: None,
+        }
+    }
+
+    /// Sets the depth, only relevant for collections.
+    ///
+    /// Servers only support `Depth::Some(0)` and [`Depth::Infinity`].
+    #[must_use]
+    pub fn depth(mut self, depth: Depth) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Requests a timeout, the server is free to choose a different one.
+    #[must_use]
+    pub fn timeout(mut self, timeout: LockTimeout) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the owner to a text `owner`.
+    #[must_use]
+    pub fn owner(self, owner: impl Into<String>) -> Self {
+        self.owner_value(PropertyValue::Text(owner.into()))
+    }
+
+    /// Sets the owner to raw XML, the prefix `d` is bound to `DAV:`.
+    #[must_use]
+    pub fn owner_xml(self, xml: impl Into<String>) -> Self {
+        self.owner_value(PropertyValue::Xml(xml.into()))
+    }
+
+    /// Sets the owner to `owner`.
+    #[must_use]
+    pub fn owner_value(mut self, owner: PropertyValue) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let mut body = String::new();
+        write!(
+            body,
+            r#"<?xml version="1.0"?><d:lockinfo xmlns:d="DAV:"><d:lockscope>"#
+        )
+        .unwrap();
+        match self.scope {
+            LockScope::Exclusive => write!(body, "<d:exclusive/>").unwrap(),
+            LockScope::Shared => write!(body, "<d:shared/>").unwrap(),
+        }
+        write!(body, "</d:lockscope><d:locktype><d:write/></d:locktype>").unwrap();
+        if let Some(owner) = &self.owner {
+            write!(body, "<d:owner>").unwrap();
+            match owner {
+                PropertyValue::Text(text) => body.push_str(&quick_xml::escape::escape(text)),
+                PropertyValue::Xml(xml) => body.push_str(xml),
+            }
+            write!(body, "</d:owner>").unwrap();
+        }
+        write!(body, "</d:lockinfo>").unwrap();
+        body
+    }
+}
+
+impl<T: WebClient<Asyncness = A>, A: Asyncness> Client<T> {
+    /// Creates a write lock on the resource at `url`.
+    ///
+    /// If the resource does not exist, an empty resource is created. The
+    /// [`token`](Lock::token) of the returned lock needs to be passed to
+    /// requests modifying the locked resources and to
+    /// [`unlock`](Self::unlock).
+    pub fn lock(&self, url: impl AsRef<str>, info: &LockInfo) -> A::Future<Result<Lock>> {
+        let mut request = self
+            .request("LOCK", url.as_ref())
+            .header(b"depth", info.depth.to_header());
+        if let Some(timeout) = info.timeout {
+            request = request.header(b"timeout", timeout.to_string().into_bytes());
+        }
+        let response = request.send_ok(Some(info.to_xml().into_bytes()));
+        Self::parse_lock(response, None)
+    }
+
+    /// Resets the timeout of the lock identified by `token` on `url`,
+    /// optionally requesting a different `timeout`.
+    pub fn refresh_lock(
+        &self,
+        url: impl AsRef<str>,
+        token: &str,
+        timeout: Option<LockTimeout>,
+    ) -> A::Future<Result<Lock>> {
+        let mut request = match Precondition::new()
+            .lock_token(token)
+            .apply(self.request("LOCK", url.as_ref()))
+        {
+            Ok(request) => request,
+            Err(e) => return A::ready(Err(e)),
+        };
+        if let Some(timeout) = timeout {
+            request = request.header(b"timeout", timeout.to_string().into_bytes());
+        }
+        Self::parse_lock(request.send_ok(None), Some(token.to_owned()))
+    }
+
+    /// Removes the lock identified by `token` from `url` and all other
+    /// resources it applies to.
+    pub fn unlock(&self, url: impl AsRef<str>, token: &str) -> A::Future<Result<()>> {
+        let response = self
+            .request("UNLOCK", url.as_ref())
+            .header(b"lock-token", format!("<{token}>").into_bytes())
+            .send_ok(None);
+        A::and_then(response, |_| Ok(()))
+    }
+
+    /// Picks the lock identified by `token` or the `Lock-Token` header from
+    /// the response, or the last one, which is usually the one just created.
+    fn parse_lock(
+        response: A::Future<Result<T::Response>>,
+        token: Option<String>,
+    ) -> A::Future<Result<Lock>> {
+        let response = A::flat_and_then(response, |response| {
+            let token = token.or_else(|| {
+                let token = response.header_str("lock-token")?.trim();
+                Some(
+                    token
+                        .strip_prefix('<')
+                        .and_then(|token| token.strip_suffix('>'))
+                        .unwrap_or(token)
+                        .to_owned(),
+                )
+            });
+            A::map(response.text(), |text| Ok((text?, token)))
+        });
+        A::and_then(response, |(s, token)| {
+            let lock_discovery: LockDiscovery = s.parse().map_err(Error::Parsing)?;
+            let mut locks = lock_discovery.locks;
+            let index = token
+                .as_ref()
+                .and_then(|token| {
+                    locks
+                        .iter()
+                        .position(|lock| lock.token.as_ref() == Some(token))
+                })
+                .or_else(|| locks.len().checked_sub(1));
+            let mut lock = index.map(|index| locks.swap_remove(index)).ok_or_else(|| {
+                Error::Parsing(quick_xml::DeError::Custom(
+                    "missing `{DAV:}activelock`".to_owned(),
+                ))
+            })?;
+            // Some servers only send the token of new locks in the header.
+            if lock.token.is_none() {
+                lock.token = token;
+            }
+            Ok(lock)
+        })
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: WebClient<Asyncness = Async>> Client<T> {
+    /// Refreshes `lock` on `url` whenever half of its timeout passed, waiting
+    /// with `sleep`, e.g., `tokio::time::sleep`.
+    ///
+    /// Only returns when a refresh fails, so it should be spawned as a task
+    /// or raced against the operation needing the lock.
+    pub async fn keep_lock<F: Future<Output = ()>>(
+        &self,
+        url: impl AsRef<str>,
+        mut lock: Lock,
+        sleep: impl Fn(Duration) -> F,
+    ) -> Result<Infallible> {
+        let token = lock_token(&lock)?;
+        loop {
+            match refresh_interval(&lock) {
+                Some(interval) => sleep(interval).await,
+                None => std::future::pending().await,
+            }
+            lock = self
+                .refresh_lock(url.as_ref(), &token, lock.timeout)
+                .await?;
+        }
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking> + Clone + Send + 'static> Client<T> {
+    /// Like [`lock`](Self::lock), but returns a guard refreshing the lock in
+    /// a background thread and releasing it when dropped.
+    pub fn lock_guard(&self, url: impl Into<String>, info: &LockInfo) -> Result<LockGuard<'_, T>> {
+        let url = url.into();
+        let lock = self.lock(&url, info)?;
+        let token = lock_token(&lock)?;
+        let shared = Arc::new(Mutex::new(Refreshed { lock, error: None }));
+        let (stop, stopped) = mpsc::channel();
+        let refresher = thread::spawn({
+            let client = self.clone();
+            let url = url.clone();
+            let token = token.clone();
+            let shared = shared.clone();
+            move || client.keep_lock_blocking(&url, &token, &shared, &stopped)
+        });
+        Ok(LockGuard {
+            client: self,
+            url,
+            token: Some(token),
+            shared,
+            refresher: Some((stop, refresher)),
+        })
+    }
+
+    /// Refreshes the lock in `shared` until `stopped` receives or is
+    /// disconnected.
+    fn keep_lock_blocking(
+        &self,
+        url: &str,
+        token: &str,
+        shared: &Mutex<Refreshed>,
+        stopped: &Receiver<()>,
+    ) {
+        loop {
+            let lock = shared
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .lock
+                .clone();
+            let waited = match refresh_interval(&lock) {
+                Some(interval) => stopped.recv_timeout(interval),
+                None => stopped.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            if waited != Err(RecvTimeoutError::Timeout) {
+                return;
+            }
+            let result = self.refresh_lock(url, token, lock.timeout);
+            let mut shared = shared.lock().unwrap_or_else(PoisonError::into_inner);
+            match result {
+                Ok(lock) => shared.lock = lock,
+                // Retried after the same interval, before the lock expires.
+                Err(error) => shared.error = Some(error),
+            }
+        }
+    }
+}
+
+/// Lock of a [`LockGuard`] shared with its refresh thread.
+struct Refreshed {
+    lock: Lock,
+    /// Error of the last failed refresh.
+    error: Option<Error>,
+}
+
+/// Lock that is refreshed in a background thread and released when dropped,
+/// see [`Client::lock_guard`].
+///
+/// The lock is refreshed whenever half of its timeout passed, errors are
+/// returned by [`refresh_error`](Self::refresh_error). Errors while
+/// unlocking on drop are ignored, use [`unlock`](Self::unlock) to handle
+/// them.
+#[must_use]
+pub struct LockGuard<'a, T: WebClient<Asyncness = Blocking>> {
+    client: &'a Client<T>,
+    url: String,
+    /// `None` once unlocked.
+    token: Option<String>,
+    shared: Arc<Mutex<Refreshed>>,
+    /// Sender stopping the refresh thread when dropped.
+    refresher: Option<(Sender<()>, JoinHandle<()>)>,
+}
+
+impl<T: WebClient<Asyncness = Blocking>> LockGuard<'_, T> {
+    /// The lock as last returned by the server.
+    #[must_use]
+    pub fn lock(&self) -> Lock {
+        self.shared().lock.clone()
+    }
+
+    /// Lock token to pass to requests modifying the locked resources.
+    #[must_use]
+    pub fn token(&self) -> &str {
+        self.token.as_deref().unwrap_or_default()
+    }
+
+    /// Resets the timeout of the lock now, optionally requesting a different
+    /// `timeout`.
+    pub fn refresh(&mut self, timeout: Option<LockTimeout>) -> Result<()> {
+        let timeout = timeout.or(self.shared().lock.timeout);
+        let lock = self.client.refresh_lock(&self.url, self.token(), timeout)?;
+        self.shared().lock = lock;
+        Ok(())
+    }
+
+    /// Takes the error of the last failed automatic refresh.
+    pub fn refresh_error(&mut self) -> Option<Error> {
+        self.shared().error.take()
+    }
+
+    /// Releases the lock.
+    pub fn unlock(mut self) -> Result<()> {
+        self.stop();
+        match self.token.take() {
+            Some(token) => self.client.unlock(&self.url, &token),
+            None => Ok(()),
+        }
+    }
+
+    fn shared(&self) -> MutexGuard<'_, Refreshed> {
+        self.shared.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stops the refresh thread, waiting for a running refresh.
+    fn stop(&mut self) {
+        if let Some((stop, refresher)) = self.refresher.take() {
+            drop(stop);
+            let _ = refresher.join();
+        }
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Drop for LockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(token) = self.token.take() {
+            let _ = self.client.unlock(&self.url, &token);
+        }
+    }
+}
+
+fn lock_token(lock: &Lock) -> Result<String> {
+    lock.token.clone().ok_or_else(|| {
+        Error::Parsing(quick_xml::DeError::Custom(
+            "missing `{DAV:}locktoken`".to_owned(),
+        ))
+    })
+}
+
+/// Time after which `lock` should be refreshed, half of its timeout.
+fn refresh_interval(lock: &Lock) -> Option<Duration> {
+    match lock.timeout {
+        Some(LockTimeout::Seconds(seconds)) => Some(Duration::from_secs(seconds.max(1)) / 2),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    const TOKEN: &str = "urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4";
+    const URL: &str = "http://example.com/workspace/webdav/proposal.doc";
+
+    /// Lock discovery of RFC 4918, section 9.10.7, with `locktoken`.
+    fn lock_discovery(locktoken: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+            <D:prop xmlns:D="DAV:">
+              <D:lockdiscovery>
+                <D:activelock>
+                  <D:locktype><D:write/></D:locktype>
+                  <D:lockscope><D:exclusive/></D:lockscope>
+                  <D:depth>infinity</D:depth>
+                  <D:owner>
+                    <D:href>http://example.org/~ejw/contact.html</D:href>
+                  </D:owner>
+                  <D:timeout>Second-604800</D:timeout>
+                  {locktoken}
+                  <D:lockroot>
+                    <D:href>{URL}</D:href>
+                  </D:lockroot>
+                </D:activelock>
+              </D:lockdiscovery>
+            </D:prop>"#
+        )
+    }
+
+    #[test]
+    fn lock_info() {
+        // RFC 4918, section 9.10.7.
+        let info = LockInfo::new(LockScope::Exclusive)
+            .owner_xml("<D:href>http://example.org/~ejw/contact.html</D:href>");
+        assert_eq!(
+            info.to_xml(),
+            r#"<?xml version="1.0"?><d:lockinfo xmlns:d="DAV:"><d:lockscope><d:exclusive/></d:lockscope><d:locktype><d:write/></d:locktype><d:owner><D:href>http://example.org/~ejw/contact.html</D:href></d:owner></d:lockinfo>"#
+        );
+        let info = LockInfo::new(LockScope::Shared).owner("Alice & Bob");
+        assert_eq!(
+            info.to_xml(),
+            r#"<?xml version="1.0"?><d:lockinfo xmlns:d="DAV:"><d:lockscope><d:shared/></d:lockscope><d:locktype><d:write/></d:locktype><d:owner>Alice &amp; Bob</d:owner></d:lockinfo>"#
+        );
+    }
+
+    #[test]
+    fn lock() {
+        let body = lock_discovery(&format!(
+            "<D:locktoken><D:href>{TOKEN}</D:href></D:locktoken>"
+        ));
+        let client = Mock::client(move |_| MockResponse::new(200).body(body.clone()));
+        let info = LockInfo::new(LockScope::Exclusive).timeout(LockTimeout::Infinite);
+        let lock = client.lock(URL, &info).unwrap();
+        assert_eq!(lock.scope, LockScope::Exclusive);
+        assert_eq!(lock.depth, Depth::Infinity);
+        assert_eq!(lock.timeout, Some(LockTimeout::Seconds(604_800)));
+        assert_eq!(lock.token.as_deref(), Some(TOKEN));
+        assert_eq!(lock.root.as_deref(), Some(URL));
+        let sent = &client.web_client.sent()[0];
+        assert_eq!(sent.method, "LOCK");
+        assert_eq!(sent.header("depth"), Some("infinity"));
+        assert_eq!(sent.header("timeout"), Some("Infinite"));
+        assert_eq!(sent.body, info.to_xml().into_bytes());
+    }
+
+    #[test]
+    fn lock_token_header() {
+        let body = lock_discovery("");
+        let client = Mock::client(move |_| {
+            MockResponse::new(200)
+                .header("Lock-Token", format!(" <{TOKEN}>"))
+                .body(body.clone())
+        });
+        let lock = client
+            .lock(URL, &LockInfo::new(LockScope::Exclusive))
+            .unwrap();
+        assert_eq!(lock.token.as_deref(), Some(TOKEN));
+    }
+
+    #[test]
+    fn lock_guard() {
+        let body = lock_discovery(&format!(
+            "<D:locktoken><D:href>{TOKEN}</D:href></D:locktoken>"
+        ));
+        let client = Mock::client(move |_| MockResponse::new(200).body(body.clone()));
+        let mut guard = client
+            .lock_guard(URL, &LockInfo::new(LockScope::Exclusive))
+            .unwrap();
+        assert_eq!(guard.token(), TOKEN);
+        guard.refresh(None).unwrap();
+        assert!(guard.refresh_error().is_none());
+        drop(guard);
+
+        let sent = client.web_client.sent();
+        let sent: Vec<_> = sent
+            .iter()
+            .map(|sent| {
+                (
+                    sent.method.as_str(),
+                    sent.header("if"),
+                    sent.header("lock-token"),
+                    sent.header("timeout"),
+                )
+            })
+            .collect();
+        let if_header = format!("(<{TOKEN}>)");
+        let lock_token = format!("<{TOKEN}>");
+        assert_eq!(sent, [
+            ("LOCK", None, None, None),
+            (
+                "LOCK",
+                Some(if_header.as_str()),
+                None,
+                Some("Second-604800")
+            ),
+            ("UNLOCK", None, Some(lock_token.as_str()), None),
+        ]);
+    }
+
+    #[test]
+    fn lock_guard_refresh() {
+        let body = lock_discovery(&format!(
+            "<D:locktoken><D:href>{TOKEN}</D:href></D:locktoken>"
+        ))
+        .replace("Second-604800", "Second-1");
+        let client = Mock::client(move |_| MockResponse::new(200).body(body.clone()));
+        let guard = client
+            .lock_guard(URL, &LockInfo::new(LockScope::Exclusive))
+            .unwrap();
+        let locked = Instant::now();
+        let refresh = loop {
+            if let Some(refresh) = client.web_client.sent().get(1).cloned() {
+                break refresh;
+            }
+            assert!(locked.elapsed() < Duration::from_secs(1), "lock expired");
+            thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(refresh.method, "LOCK");
+        assert_eq!(refresh.header("if"), Some(format!("(<{TOKEN}>)").as_str()));
+        assert_eq!(refresh.header("timeout"), Some("Second-1"));
+        assert_eq!(guard.lock().timeout, Some(LockTimeout::Seconds(1)));
+        drop(guard);
+        assert_eq!(client.web_client.sent().last().unwrap().method, "UNLOCK");
+    }
+}
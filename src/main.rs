@@ -1,13 +1,15 @@
 //! Commandline tool to interact with WebDAV servers.
 #![allow(clippy::needless_pass_by_value)]
 use std::collections::HashMap;
-use std::env;
 use std::fmt::Write as _;
-use std::io::{stdin, stdout};
+use std::fs::{self, File};
+use std::io::{self, stdin, stdout};
 use std::num::ParseIntError;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::process::ExitCode;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::{env, thread};
 
 use anyhow::{Context as _, Error, Result, anyhow, bail};
 use clap::{CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
@@ -17,11 +19,26 @@ use derive_more::Error;
 use derive_more::derive::{Display, FromStr};
 use humansize::DECIMAL;
 use percent_encoding::percent_decode;
-use reqwest::blocking::Body;
 use time::OffsetDateTime;
-use time::format_description::well_known::{Rfc2822, Rfc3339};
-use webdav_client::webdav_types::{PropValue, Response};
-use webdav_client::{Auth, Depth, Request};
+use time::format_description::well_known::Rfc3339;
+use webdav_client::webdav_types::namespace::{DAV, NEXTCLOUD, OWNCLOUD};
+use webdav_client::webdav_types::{PropValue, PropertyError, QualifiedName, Response};
+use webdav_client::{
+    Auth, Blocking, Body, ChunkedUpload, ConflictPolicy, Depth, Precondition, PropFind,
+    Response as _, SyncOptions, SyncState,
+};
+
+/// File name of the default sync state, excluded from syncing.
+const SYNC_STATE: &str = ".webdav-sync-state";
+
+/// Namespace prefixes predefined for `list --fields`.
+const NAMESPACES: [(&str, &str); 5] = [
+    ("d", DAV),
+    ("oc", OWNCLOUD),
+    ("nc", NEXTCLOUD),
+    ("ocs", "http://open-collaboration-services.org/ns"),
+    ("ocm", "http://open-cloud-mesh.org/ns"),
+];
 
 #[derive(Debug, Error, Display)]
 #[display("{_0}")]
@@ -36,7 +53,6 @@ impl From<Error> for ExitCodeError {
 #[derive(Clone, Debug)]
 struct Client {
     inner: webdav_client::Client<reqwest::blocking::Client>,
-    host: String,
 }
 
 impl Client {
@@ -51,36 +67,18 @@ impl Client {
         };
 
         Self {
-            inner: webdav_client::Client::authenticated(reqwest::blocking::Client::new(), auth),
-            host,
+            inner: webdav_client::Client::authenticated(reqwest::blocking::Client::new(), auth)
+                .with_base_url(host),
         }
     }
 
+    /// Paths are relative to the host, even when starting with `/`.
     fn path(&self, path: &str) -> String {
-        if self.host.ends_with('/') && path.starts_with('/') {
-            self.host.clone() + path.trim_start_matches('/')
-        } else if self.host.ends_with('/') || path.starts_with('/') {
-            self.host.clone() + path
-        } else {
-            self.host.clone() + "/" + path
-        }
+        self.inner.url(path.trim_start_matches('/'))
     }
 
     fn list(&self, path: &str, depth: Depth, fields: &[ListField]) -> Result<()> {
-        let mut namespaces = HashMap::new();
-        let names = fields
-            .iter()
-            .flat_map(|field| field.to_xml(&mut namespaces))
-            .collect::<Result<Vec<String>>>()?;
-        let namespaces: Vec<_> = namespaces
-            .iter()
-            .map(|(key, value)| (key.as_str(), value.as_str()))
-            .chain([
-                ("d", "DAV:"),
-                ("oc", "http://owncloud.org/ns"),
-                ("nc", "http://nextcloud.org/ns"),
-            ])
-            .collect();
+        let (names, namespaces) = ListField::prop_find_fields(fields)?;
         let url = self.path(path);
         let xml = self.inner.prop_find(&url, depth, &names, namespaces);
         if let Err(e) = xml {
@@ -91,7 +89,7 @@ impl Client {
                 ))
             }
             bail!(e)
-        };
+        }
         let xml = xml?;
 
         let mut table = Table::new();
@@ -114,57 +112,461 @@ impl Client {
         Ok(())
     }
 
-    fn get(&self, path: String, out_path: Option<PathBuf>) -> Result<()> {
-        let result = self.inner.get_raw(self.path(&path));
-        match result {
-            Ok(mut result) => {
-                if let Some(out_path) = out_path {
-                    result.copy_to(&mut std::fs::File::create_new(&out_path).with_context(
-                        || format!("Could not create file for output `{}`", out_path.display()),
-                    )?)?;
-                } else {
-                    result.copy_to(&mut stdout())?;
-                }
-                Ok(())
+    fn get(
+        &self,
+        path: String,
+        out_path: Option<PathBuf>,
+        resume: bool,
+        transfer: &Transfer,
+    ) -> Result<()> {
+        let existing = if resume {
+            Existing::Continue
+        } else {
+            transfer.existing(Existing::Fail)
+        };
+        if transfer.recursive {
+            let out_path = out_path.unwrap_or_else(|| {
+                PathBuf::from(path.rsplit('/').find(|s| !s.is_empty()).unwrap_or("."))
+            });
+            return self.get_recursive(&path, &out_path, existing, transfer.jobs);
+        }
+        let url = self.path(&path);
+        let Some(out_path) = out_path else {
+            if resume {
+                bail!("`--continue` requires `--out-path` or `--recursive`");
             }
-            Err(e) if e.is_not_found() => bail!("404 Not Found {}", self.path(&path)),
-            other => {
-                other?;
-                Ok(())
+            let result = self.inner.get_raw(&url, None);
+            match result {
+                Ok(result) => io::copy(&mut result.stream(), &mut stdout())?,
+                Err(e) if e.is_not_found() => bail!("404 Not Found {url}"),
+                Err(e) => bail!(e),
+            };
+            return Ok(());
+        };
+        let modified = if existing == Existing::Newer && out_path.exists() {
+            self.remote_modified(&url)?
+        } else {
+            None
+        };
+        self.download(&url, &out_path, existing, modified)
+    }
+
+    /// Mirrors the collection at `path` to the directory `out_path`.
+    fn get_recursive(
+        &self,
+        path: &str,
+        out_path: &Path,
+        existing: Existing,
+        jobs: usize,
+    ) -> Result<()> {
+        let url = self.path(path);
+        fs::create_dir_all(out_path)
+            .with_context(|| format!("Could not create directory `{}`", out_path.display()))?;
+        let mut files = Vec::new();
+        for entry in self.inner.walk(&url) {
+            let entry = entry.map_err(|e| status_error(e, &url))?;
+            let local = local_path(out_path, &entry.path)?;
+            if entry.is_collection() {
+                fs::create_dir_all(&local)
+                    .with_context(|| format!("Could not create directory `{}`", local.display()))?;
+            } else {
+                files.push((entry.url, local, entry.properties.last_modified));
             }
         }
+        parallel(files, jobs, |(url, local, modified)| {
+            self.download(&url, &local, existing, modified)
+        })
     }
 
-    fn put(&self, path: String, in_path: Option<PathBuf>) -> Result<()> {
-        let request = self.inner.put_raw(self.path(&path));
-        if let Err(e) = Request::send_ok(
-            if let Some(in_path) = in_path {
-                request.body(std::fs::File::open(&in_path).with_context(|| {
-                    format!("Could not read input file `{}`", in_path.display())
-                })?)
-            } else {
-                request.body(Body::new(stdin()))
-            },
-            None,
-        ) {
-            if e.is_conflict() {
-                bail!("409 Conflict (probably a directory) {}", self.path(&path))
+    /// Downloads `url` to `out_path`, `modified` is the remote modification
+    /// time used for [`Existing::Newer`] and applied to the file.
+    fn download(
+        &self,
+        url: &str,
+        out_path: &Path,
+        existing: Existing,
+        modified: Option<OffsetDateTime>,
+    ) -> Result<()> {
+        if existing == Existing::Continue {
+            self.inner
+                .download_resumable(url, out_path)
+                .map_err(|e| status_error(e, url))?;
+            if let Some(modified) = modified {
+                File::options()
+                    .write(true)
+                    .open(out_path)?
+                    .set_modified(modified.into())?;
             }
-            if e.is_not_found() {
-                bail!(ExitCodeError(
-                    44,
-                    anyhow!(
-                        "404 Not Found (probably parent directory non-existent) {}",
-                        self.path(&path)
-                    )
-                ))
+            return Ok(());
+        }
+        if out_path.exists() {
+            match existing {
+                Existing::Skip => return Ok(()),
+                Existing::Newer => {
+                    let local = fs::metadata(out_path)?.modified()?;
+                    if modified.is_none_or(|modified| modified <= local) {
+                        return Ok(());
+                    }
+                }
+                Existing::Fail | Existing::Overwrite | Existing::Continue => {}
             }
-            bail!(e)
+        }
+        let context = || format!("Could not create file for output `{}`", out_path.display());
+        // Fail before sending the request if the file exists.
+        let file = if existing == Existing::Fail {
+            Some(File::create_new(out_path).with_context(context)?)
+        } else {
+            None
+        };
+        let result = match self.inner.get_raw(url, None) {
+            Ok(result) => result,
+            Err(e) => {
+                if file.is_some() {
+                    fs::remove_file(out_path)?;
+                }
+                if e.is_not_found() {
+                    bail!("404 Not Found {url}");
+                }
+                bail!(e);
+            }
+        };
+        let mut file = match file {
+            Some(file) => file,
+            None => File::create(out_path).with_context(context)?,
+        };
+        io::copy(&mut result.stream(), &mut file)?;
+        if let Some(modified) = modified {
+            file.set_modified(modified.into())?;
+        }
+        Ok(())
+    }
+
+    fn put(
+        &self,
+        path: String,
+        in_path: Option<PathBuf>,
+        chunk_size: u64,
+        transfer: &Transfer,
+    ) -> Result<()> {
+        let existing = transfer.existing(Existing::Overwrite);
+        if transfer.recursive {
+            let in_path = in_path.context("`--recursive` requires `--in-path`")?;
+            return self.put_recursive(&path, &in_path, existing, chunk_size, transfer.jobs);
+        }
+        let url = self.path(&path);
+        let Some(in_path) = in_path else {
+            return self.upload(&url, Body::reader(stdin(), None), existing);
         };
+        if existing == Existing::Newer {
+            let local = fs::metadata(&in_path)
+                .with_context(|| format!("Could not read input file `{}`", in_path.display()))?
+                .modified()?;
+            if self
+                .remote_modified(&url)?
+                .is_some_and(|remote| remote >= local)
+            {
+                return Ok(());
+            }
+        }
+        self.upload_file(&url, &in_path, existing, chunk_size)
+    }
+
+    /// Mirrors the directory `in_path` to the collection at `path`.
+    fn put_recursive(
+        &self,
+        path: &str,
+        in_path: &Path,
+        existing: Existing,
+        chunk_size: u64,
+        jobs: usize,
+    ) -> Result<()> {
+        let path = path.trim_end_matches('/');
+        self.mkcol(path, true)?;
+        let mut remote = HashMap::new();
+        if matches!(existing, Existing::Skip | Existing::Newer) {
+            let url = self.path(path);
+            for entry in self.inner.walk(&url) {
+                let entry = entry.map_err(|e| status_error(e, &url))?;
+                remote.insert(entry.path, entry.properties.last_modified);
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut directories = vec![(in_path.to_owned(), String::new())];
+        while let Some((directory, relative)) = directories.pop() {
+            let entries = fs::read_dir(&directory).with_context(|| {
+                format!("Could not read input directory `{}`", directory.display())
+            })?;
+            for entry in entries {
+                let entry = entry?;
+                let name = entry
+                    .file_name()
+                    .into_string()
+                    .map_err(|name| anyhow!("File name is not valid UTF-8 {}", name.display()))?;
+                let relative = if relative.is_empty() {
+                    name
+                } else {
+                    format!("{relative}/{name}")
+                };
+                // File names are not percent-encoded, so a literal `%` must not
+                // be read as an escape.
+                let target = format!("{path}/{}", relative.replace('%', "%25"));
+                // Symbolic links are skipped, following them could loop.
+                let metadata = fs::symlink_metadata(entry.path())?;
+                if metadata.is_symlink() {
+                    continue;
+                }
+                if metadata.is_dir() {
+                    if !remote.contains_key(&relative) {
+                        self.ensure_collection(&target)?;
+                    }
+                    directories.push((entry.path(), relative));
+                    continue;
+                }
+                let skip = match (existing, remote.get(&relative)) {
+                    (Existing::Skip, Some(_)) => true,
+                    (Existing::Newer, Some(Some(remote))) => *remote >= metadata.modified()?,
+                    _ => false,
+                };
+                if !skip {
+                    files.push((self.path(&target), entry.path()));
+                }
+            }
+        }
+        parallel(files, jobs, |(url, in_path)| {
+            self.upload_file(&url, &in_path, existing, chunk_size)
+        })
+    }
+
+    /// Uploads the file `in_path` to `url`, in chunks if it is larger than
+    /// `chunk_size` and `url` supports chunked uploads.
+    fn upload_file(
+        &self,
+        url: &str,
+        in_path: &Path,
+        existing: Existing,
+        chunk_size: u64,
+    ) -> Result<()> {
+        let file = File::open(in_path)
+            .with_context(|| format!("Could not read input file `{}`", in_path.display()))?;
+        let length = file.metadata()?.len();
+        if chunk_size == 0
+            || length <= chunk_size
+            || ChunkedUpload::default_uploads_url(url).is_none()
+        {
+            return self.upload(url, Body::reader(file, Some(length)), existing);
+        }
+        let options = ChunkedUpload::new()
+            .chunk_size(chunk_size)
+            .overwrite(!matches!(existing, Existing::Fail | Existing::Skip));
+        match self.inner.chunked_upload(url, file, &options) {
+            Ok(_) => Ok(()),
+            Err(e) if e.is_precondition_failed() && existing == Existing::Skip => Ok(()),
+            Err(e) => Err(status_error(e, url)),
+        }
+    }
+
+    /// Uploads `body` to `url`, using `If-None-Match: *` to not replace
+    /// existing files unless `existing` allows it.
+    fn upload(&self, url: &str, body: Body<Blocking>, existing: Existing) -> Result<()> {
+        let precondition = Precondition::new().if_none_match_any();
+        let precondition =
+            matches!(existing, Existing::Fail | Existing::Skip).then_some(&precondition);
+        match self.inner.put(url, body, precondition) {
+            Ok(_) => Ok(()),
+            Err(e) if e.is_precondition_failed() && existing == Existing::Skip => Ok(()),
+            Err(e) if e.is_conflict() => bail!("409 Conflict (probably a directory) {url}"),
+            Err(e) if e.is_not_found() => bail!(ExitCodeError(
+                44,
+                anyhow!("404 Not Found (probably parent directory non-existent) {url}")
+            )),
+            Err(e) => Err(status_error(e, url)),
+        }
+    }
+
+    /// Last modification time of the resource at `url`, `None` if it does
+    /// not exist.
+    fn remote_modified(&self, url: &str) -> Result<Option<OffsetDateTime>> {
+        let prop_find = PropFind::Prop(vec![QualifiedName::dav("getlastmodified")]);
+        match self.inner.prop_find_with(url, Depth::Some(0), &prop_find) {
+            Ok(multi_status) => Ok(multi_status
+                .responses
+                .first()
+                .map(Response::last_modified)
+                .transpose()?
+                .flatten()),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(status_error(e, url)),
+        }
+    }
+
+    fn sync(
+        &self,
+        local: &Path,
+        remote: &str,
+        dry_run: bool,
+        options: SyncOptions,
+        state_path: Option<PathBuf>,
+    ) -> Result<()> {
+        let state_path = state_path.unwrap_or_else(|| local.join(SYNC_STATE));
+        let mut state = SyncState::load(&state_path)
+            .with_context(|| format!("Could not read sync state `{}`", state_path.display()))?;
+        let url = self.path(remote);
+        let plan = self
+            .inner
+            .sync_plan(local, &url, &state, &options)
+            .map_err(|e| status_error(e, &url))?;
+        for action in &plan.actions {
+            println!("{action}");
+        }
+        if dry_run {
+            return Ok(());
+        }
+        let result = self.inner.sync(&plan, &mut state);
+        state
+            .save(&state_path)
+            .with_context(|| format!("Could not write sync state `{}`", state_path.display()))?;
+        result.map_err(|e| status_error(e, &url))
+    }
+
+    fn delete(&self, path: &str) -> Result<()> {
+        let url = self.path(path);
+        self.inner
+            .delete(&url, None)
+            .map_err(|e| status_error(e, &url))
+    }
+
+    fn mkcol(&self, path: &str, parents: bool) -> Result<()> {
+        if parents {
+            self.create_parents(path)?;
+        }
+        let url = self.path(path);
+        match self.inner.mkcol(&url, None) {
+            Err(webdav_client::Error::ErrorStatus { status: 405, .. }) if parents => Ok(()),
+            result => result.map_err(|e| status_error(e, &url)),
+        }
+    }
+
+    /// Creates all missing parent collections of `path`, similar to `mkdir
+    /// -p`.
+    fn create_parents(&self, path: &str) -> Result<()> {
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let mut parent = String::new();
+        for segment in &segments[..segments.len().saturating_sub(1)] {
+            parent = parent + "/" + segment;
+            self.ensure_collection(&parent)?;
+        }
+        Ok(())
+    }
+
+    /// Creates the collection at `path` unless it already exists.
+    fn ensure_collection(&self, path: &str) -> Result<()> {
+        let url = self.path(path);
+        match self.inner.mkcol(&url, None) {
+            // 405 Method Not Allowed is returned for existing collections
+            Ok(()) | Err(webdav_client::Error::ErrorStatus { status: 405, .. }) => Ok(()),
+            Err(e) => Err(status_error(e, &url)),
+        }
+    }
+
+    fn transfer(
+        &self,
+        copy: bool,
+        source: &str,
+        destination: &str,
+        overwrite: bool,
+        depth: Depth,
+        parents: bool,
+    ) -> Result<()> {
+        if parents {
+            self.create_parents(destination)?;
+        }
+        let url = self.path(source);
+        let destination = self.path(destination);
+        if copy {
+            self.inner
+                .copy_to(&url, &destination, depth, overwrite, None)
+        } else {
+            self.inner.move_to(&url, &destination, overwrite, None)
+        }
+        .map_err(|e| status_error(e, &format!("{url} -> {destination}")))?;
         Ok(())
     }
 }
 
+/// Joins the `/` separated `path` returned by the server to `base`, rejecting
+/// paths that would escape it.
+fn local_path(base: &Path, path: &str) -> Result<PathBuf> {
+    let path = Path::new(path);
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        bail!(
+            "Refusing to write outside of `{}`: {}",
+            base.display(),
+            path.display()
+        );
+    }
+    Ok(base.join(path))
+}
+
+/// Runs `run` for all `jobs` on up to `parallel` threads, stopping at the
+/// first error.
+fn parallel<J: Send>(
+    jobs: Vec<J>,
+    parallel: usize,
+    run: impl Fn(J) -> Result<()> + Sync,
+) -> Result<()> {
+    let jobs = Mutex::new(jobs.into_iter());
+    let error = Mutex::new(None);
+    thread::scope(|scope| {
+        for _ in 0..parallel.max(1) {
+            scope.spawn(|| {
+                while error.lock().unwrap().is_none() {
+                    let Some(job) = jobs.lock().unwrap().next() else {
+                        break;
+                    };
+                    if let Err(e) = run(job) {
+                        error.lock().unwrap().get_or_insert(e);
+                    }
+                }
+            });
+        }
+    });
+    error.into_inner().unwrap().map_or(Ok(()), Err)
+}
+
+/// Maps common WebDAV error status codes to distinct exit codes.
+///
+/// The exit code is `4` followed by the last digit of the HTTP status code,
+/// i.e., 404 → 44, 409 → 49, 412 → 42 and 423 → 43.
+fn status_error(error: webdav_client::Error, context: &str) -> Error {
+    let (code, message) = match &error {
+        webdav_client::Error::ErrorStatus { status, .. } => match status {
+            404 => (44, "404 Not Found"),
+            409 => (49, "409 Conflict (probably parent directory non-existent)"),
+            423 => (43, "423 Locked"),
+            _ => return error.into(),
+        },
+        webdav_client::Error::PreconditionFailed { .. } => {
+            (42, "412 Precondition Failed (destination already exists)")
+        }
+        webdav_client::Error::MultiStatus(multi_status) => {
+            let mut message = format!("Operation failed for some resources of {context}:");
+            for response in multi_status.responses.iter().filter(|r| !r.is_successful()) {
+                write!(message, "\n  {}", response.href).unwrap();
+                if let Some(status) = &response.status {
+                    write!(message, " {} {}", status.code, status.reason).unwrap();
+                }
+            }
+            return anyhow!(message);
+        }
+        _ => return error.into(),
+    };
+    ExitCodeError(code, anyhow!("{message} {context}")).into()
+}
+
 fn replace_env(mut help: String) -> String {
     fn shorten(s: String) -> String {
         let max_len = 32;
@@ -197,10 +599,10 @@ fn replace_env(mut help: String) -> String {
 fn main() -> Result<ExitCode> {
     let command = Args::command_for_update().mut_args(|mut a| {
         if let Some(help) = a.get_help().map(|s| s.ansi().to_string()) {
-            a = a.help(replace_env(help.to_string()));
+            a = a.help(replace_env(help));
         }
         if let Some(help) = a.get_long_help().map(|s| s.ansi().to_string()) {
-            a = a.help(replace_env(help.to_string()));
+            a = a.help(replace_env(help));
         }
         a
     });
@@ -214,12 +616,56 @@ fn main() -> Result<ExitCode> {
     let client = Client::new(host, login, password);
 
     if let Err(e) = match action {
-        Action::Get { path, out_path } => client.get(path, out_path),
-        Action::Put { path, in_path } => client.put(path, in_path),
-        Action::Delete => todo!(),
-        Action::Mkcol => todo!(),
-        Action::Move => todo!(),
-        Action::Copy => todo!(),
+        Action::Get {
+            path,
+            out_path,
+            resume,
+            transfer,
+        } => client.get(path, out_path, resume, &transfer),
+        Action::Put {
+            path,
+            in_path,
+            chunk_size,
+            transfer,
+        } => client.put(path, in_path, chunk_size, &transfer),
+        Action::Sync {
+            local,
+            remote,
+            dry_run,
+            conflict,
+            state,
+            exclude,
+        } => {
+            let options = exclude.into_iter().fold(
+                SyncOptions::new()
+                    .conflict_policy(conflict.into())
+                    .exclude(SYNC_STATE),
+                SyncOptions::exclude,
+            );
+            client.sync(&local, &remote, dry_run, options, state)
+        }
+        Action::Delete { path } => client.delete(&path),
+        Action::Mkcol { path, parents } => client.mkcol(&path, parents),
+        Action::Move {
+            source,
+            destination,
+            no_overwrite,
+            parents,
+        } => client.transfer(
+            false,
+            &source,
+            &destination,
+            !no_overwrite,
+            Depth::Infinity,
+            parents,
+        ),
+        Action::Copy {
+            source,
+            destination,
+            no_overwrite,
+            depth,
+            parents,
+        } => client.transfer(true, &source, &destination, !no_overwrite, depth, parents),
         Action::List {
             path,
             depth,
@@ -247,6 +693,84 @@ impl std::fmt::Debug for Password {
     }
 }
 
+// Options of `get` and `put`.
+#[derive(clap::Args, Debug)]
+struct Transfer {
+    /// Transfer directories recursively, creating missing directories.
+    ///
+    /// Symbolic links are skipped when uploading.
+    #[clap(long, short)]
+    recursive: bool,
+    #[clap(flatten)]
+    existing: ExistingArgs,
+    /// Number of files transferred in parallel with `--recursive`.
+    #[clap(long, short, default_value = "4")]
+    jobs: usize,
+}
+
+#[derive(clap::Args, Debug)]
+#[group(multiple = false)]
+struct ExistingArgs {
+    /// Replace existing files, the default for `put`.
+    #[clap(long)]
+    overwrite: bool,
+    /// Skip existing files.
+    #[clap(long)]
+    skip_existing: bool,
+    /// Only replace existing files modified before the source.
+    #[clap(long)]
+    newer: bool,
+}
+
+impl Transfer {
+    fn existing(&self, default: Existing) -> Existing {
+        let ExistingArgs {
+            overwrite,
+            skip_existing,
+            newer,
+        } = self.existing;
+        if overwrite {
+            Existing::Overwrite
+        } else if skip_existing {
+            Existing::Skip
+        } else if newer {
+            Existing::Newer
+        } else {
+            default
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Conflict {
+    /// Keep the remote file and upload the local one as a conflict copy.
+    KeepBoth,
+    /// Replace the remote file.
+    LocalWins,
+    /// Replace the local file.
+    RemoteWins,
+}
+
+impl From<Conflict> for ConflictPolicy {
+    fn from(value: Conflict) -> Self {
+        match value {
+            Conflict::KeepBoth => ConflictPolicy::KeepBoth,
+            Conflict::LocalWins => ConflictPolicy::LocalWins,
+            Conflict::RemoteWins => ConflictPolicy::RemoteWins,
+        }
+    }
+}
+
+/// How to handle files already existing at the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Existing {
+    Fail,
+    Overwrite,
+    Skip,
+    Newer,
+    Continue,
+}
+
 /// Utility to interact with WebDAV servers.
 #[derive(Parser, Debug)]
 struct Args {
@@ -270,18 +794,87 @@ enum Action {
         path: String,
         #[clap(long, short)]
         out_path: Option<PathBuf>,
+        /// Continue partially downloaded files, starting over if the remote
+        /// file changed in between, requires `--out-path` or `--recursive`.
+        #[clap(long = "continue", short = 'c', conflicts_with = "ExistingArgs")]
+        resume: bool,
+        #[clap(flatten)]
+        transfer: Transfer,
     },
     Put {
         #[clap(default_value = "/")]
         path: String,
         #[clap(long, short)]
         in_path: Option<PathBuf>,
+        /// Upload files larger than this in chunks of this size (in bytes) to
+        /// Nextcloud and ownCloud, at least `5242880`, `0` disables chunked
+        /// uploads.
+        #[clap(long, value_parser = parse_chunk_size, default_value = "10485760")]
+        chunk_size: u64,
+        #[clap(flatten)]
+        transfer: Transfer,
     },
-    Delete,
+    /// Synchronize a local directory and a remote directory in both
+    /// directions.
+    ///
+    /// The state of the last sync is stored to tell deleted files apart from
+    /// new ones.
+    Sync {
+        local: PathBuf,
+        remote: String,
+        /// Only print the planned changes.
+        #[clap(long, short = 'n')]
+        dry_run: bool,
+        /// How to resolve files changed on both sides.
+        #[clap(long, value_enum, default_value = "keep-both")]
+        conflict: Conflict,
+        /// File storing the state of the last sync [default:
+        /// LOCAL/.webdav-sync-state]
+        #[clap(long)]
+        state: Option<PathBuf>,
+        /// Exclude files and directories matching a glob, e.g., `*.tmp`.
+        #[clap(long, short)]
+        exclude: Vec<String>,
+    },
+    /// Delete a file or directory, directories are deleted recursively.
+    #[clap(alias = "rm")]
+    Delete { path: String },
+    /// Create a directory.
     #[clap(alias = "mkdir")]
-    Mkcol,
-    Move,
-    Copy,
+    Mkcol {
+        path: String,
+        /// Create missing parent directories.
+        #[clap(long = "recursive", short = 'p')]
+        parents: bool,
+    },
+    /// Move a file or directory.
+    #[clap(alias = "mv")]
+    Move {
+        source: String,
+        destination: String,
+        /// Fail if the destination already exists.
+        #[clap(long)]
+        no_overwrite: bool,
+        /// Create missing parent directories of the destination.
+        #[clap(long = "recursive", short = 'p')]
+        parents: bool,
+    },
+    /// Copy a file or directory.
+    #[clap(alias = "cp")]
+    Copy {
+        source: String,
+        destination: String,
+        /// Fail if the destination already exists.
+        #[clap(long)]
+        no_overwrite: bool,
+        /// Depth to copy directories with, either `0` (only the directory
+        /// and its properties) or `infinity`.
+        #[clap(long, short, value_parser = parse_depth, default_value = "infinity")]
+        depth: Depth,
+        /// Create missing parent directories of the destination.
+        #[clap(long = "recursive", short = 'p')]
+        parents: bool,
+    },
     /// List files and their properties.
     List {
         #[clap(default_value = "/")]
@@ -377,6 +970,9 @@ enum ListField {
     },
 }
 
+/// Namespace declarations as `(prefix, URI)`.
+type Namespaces = Vec<(String, String)>;
+
 #[allow(clippy::unnecessary_wraps)]
 fn parse_list_fields(value: &str) -> Result<ListField> {
     Ok(match ListField::from_str(value, true) {
@@ -402,6 +998,28 @@ fn parse_list_fields(value: &str) -> Result<ListField> {
 }
 
 impl ListField {
+    /// Returns the property names to request for `fields` and the namespace
+    /// declarations (prefix, URI) they require.
+    fn prop_find_fields(fields: &[ListField]) -> Result<(Vec<String>, Namespaces)> {
+        let mut namespaces = HashMap::new();
+        let names = fields
+            .iter()
+            .flat_map(|field| field.to_xml(&mut namespaces))
+            .collect::<Result<Vec<String>>>()?;
+        let namespaces = namespaces
+            .into_iter()
+            .map(|(uri, prefix)| (prefix, uri))
+            .chain(
+                NAMESPACES
+                    .iter()
+                    .map(|&(prefix, uri)| (prefix.to_owned(), uri.to_owned())),
+            )
+            .collect();
+        Ok((names, namespaces))
+    }
+
+    /// Returns the qualified names to request for this field, `namespaces`
+    /// maps namespace URIs of custom fields to their generated prefixes.
     fn to_xml(&self, namespaces: &mut HashMap<String, String>) -> Vec<anyhow::Result<String>> {
         vec![Ok(match self {
             ListField::Path | ListField::AbsolutePath => return vec![],
@@ -437,7 +1055,7 @@ impl ListField {
                     let namespace_name = namespaces
                         .entry(namespace_uri.to_owned())
                         .or_insert_with(|| format!("webdav-client-ns{len}"));
-                    return vec![Ok(format!("{namespace_name}:name"))];
+                    return vec![Ok(format!("{namespace_name}:{name}"))];
                 }
                 name
             }
@@ -450,59 +1068,46 @@ impl ListField {
         if let Self::AbsolutePath = self {
             Some(response.href.clone())
         } else if let Self::Path = self {
-            let response = 'response: {
-                let mut prefix = prefix.trim_end_matches('/');
-                if let Some(response) = response.href.strip_prefix(prefix) {
-                    break 'response response;
-                };
-                while !prefix.is_empty() {
-                    if let Some(response) = response
-                        .href
-                        .strip_prefix(prefix)
-                        .or_else(|| response.href.strip_prefix(prefix.strip_prefix('/')?))
-                    {
-                        break 'response response;
-                    }
-                    prefix = prefix.strip_prefix('/').unwrap_or(prefix);
-                    if let Some(index) = prefix.find('/') {
-                        prefix = &prefix[index..];
-                        continue;
-                    }
-                    break;
-                }
-                &response.href
-            }
-            .trim_start_matches('/');
-            if response.is_empty() {
-                return Some(".".to_owned());
-            }
-            percent_decode(response.as_bytes())
-                .decode_utf8()
-                .map(String::from)
-                .ok()
-                .or_else(|| Some(response.to_string()))
+            let Some(path) = response.relative_path(prefix) else {
+                return Some(
+                    percent_decode(response.href.as_bytes())
+                        .decode_utf8_lossy()
+                        .into_owned(),
+                );
+            };
+            Some(if path.is_empty() {
+                ".".to_owned()
+            } else if response.href.ends_with('/') {
+                path + "/"
+            } else {
+                path
+            })
         } else {
             let successful = response
                 .propstat
                 .iter()
                 .find(|ps| ps.status.is_successful())?;
 
-            let ref_value = |name: &str| successful.prop.get(name)?.try_unwrap_text_ref().ok();
-            let get_value = |name: &str| ref_value(name).cloned();
-            let get_number = |name: &str| {
-                usize::from_str(ref_value(name).or_else(|| ref_value("getcontentlength"))?).ok()
+            let ref_value = |namespace: &str, name: &str| {
+                successful
+                    .get(namespace, name)?
+                    .try_unwrap_text_ref()
+                    .ok()
+                    .map(|text| text.trim())
             };
-
-            let parse_date = |name: &str| {
-                let value = ref_value(name)?;
-                Some(
-                    OffsetDateTime::parse(value, &Rfc3339)
-                        .or_else(|_| OffsetDateTime::parse(value, &Rfc2822))
-                        .map_or_else(
-                            |_| value.clone(),
-                            |date| date.format(&Rfc3339).unwrap_or_else(|_| value.clone()),
-                        ),
+            let get_value =
+                |namespace: &str, name: &str| ref_value(namespace, name).map(str::to_owned);
+            let get_number = |namespace: &str, name: &str| {
+                usize::from_str(
+                    ref_value(namespace, name).or_else(|| ref_value(DAV, "getcontentlength"))?,
                 )
+                .ok()
+            };
+
+            // Falls back to the raw value for unparsable dates.
+            let format_date = |date: Result<Option<OffsetDateTime>, PropertyError>| match date {
+                Ok(date) => date?.format(&Rfc3339).ok(),
+                Err(e) => Some(e.value),
             };
 
             fn to_xml(prop_value: &PropValue, out: &mut String) {
@@ -511,6 +1116,7 @@ impl ListField {
                     PropValue::Text(text) => out.push_str(text),
                     PropValue::Xml(hash_map) => {
                         for (name, value) in hash_map {
+                            let name = &name.name;
                             for value in value {
                                 write!(out, "<{name}>").unwrap();
                                 to_xml(value, out);
@@ -523,41 +1129,29 @@ impl ListField {
 
             match self {
                 ListField::AbsolutePath | ListField::Path => unreachable!(),
-                ListField::Name => todo!(),
-                ListField::CreatedAt => parse_date("creationdate"),
-                ListField::ModifiedAt => parse_date("getlastmodified"),
-                ListField::ContentType => get_value("getcontenttype"),
-                ListField::ResourceType => get_value("resourcetype"),
-                ListField::ContentLength => get_value("getcontentlength"),
+                ListField::Name => response.display_name().unwrap_or_default(),
+                ListField::CreatedAt => format_date(response.creation_date()),
+                ListField::ModifiedAt => format_date(response.last_modified()),
+                ListField::ContentType => get_value(DAV, "getcontenttype"),
+                ListField::ResourceType => get_value(DAV, "resourcetype"),
+                ListField::ContentLength => get_value(DAV, "getcontentlength"),
                 ListField::Size => Some(humansize::format_size(
-                    get_number("size").or_else(|| get_number("getcontentlength"))?,
+                    get_number(OWNCLOUD, "size").or_else(|| get_number(DAV, "getcontentlength"))?,
                     DECIMAL,
                 )),
                 ListField::FolderEntryCount => Some(
-                    (get_number("contained-file-count").unwrap_or_default()
-                        + get_number("contained-folder-count").unwrap_or_default())
+                    (get_number(NEXTCLOUD, "contained-file-count").unwrap_or_default()
+                        + get_number(NEXTCLOUD, "contained-folder-count").unwrap_or_default())
                     .to_string(),
                 ),
                 ListField::Tags => {
                     let system_tags = successful
-                        .prop
-                        .get("system-tags")
-                        .and_then(|tags| {
-                            tags.try_unwrap_xml_ref()
-                                .ok()?
-                                .get("system-tag")
-                                .map(Vec::as_slice)
-                        })
+                        .get(NEXTCLOUD, "system-tags")
+                        .and_then(|tags| tags.get(NEXTCLOUD, "system-tag"))
                         .unwrap_or_default();
                     let tags = successful
-                        .prop
-                        .get("tags")
-                        .and_then(|tags| {
-                            tags.try_unwrap_xml_ref()
-                                .ok()?
-                                .get("tag")
-                                .map(Vec::as_slice)
-                        })
+                        .get(OWNCLOUD, "tags")
+                        .and_then(|tags| tags.get(OWNCLOUD, "tag"))
                         .unwrap_or_default();
                     Some(
                         system_tags
@@ -576,16 +1170,21 @@ impl ListField {
                             }),
                     )
                 }
-                ListField::OwnerId => get_value("owner-id"),
-                ListField::OwnerName => get_value("owner-display-name"),
-                ListField::Other { name, .. } => {
+                ListField::OwnerId => get_value(OWNCLOUD, "owner-id"),
+                ListField::OwnerName => get_value(OWNCLOUD, "owner-display-name"),
+                ListField::Other {
+                    namespace_uri,
+                    name,
+                } => {
+                    let (namespace, name) = match (namespace_uri, name.split_once(':')) {
+                        (Some(namespace_uri), _) => (namespace_uri.as_str(), name.as_str()),
+                        (None, Some((prefix, name))) => {
+                            (NAMESPACES.iter().find(|ns| ns.0 == prefix)?.1, name)
+                        }
+                        (None, None) => ("", name.as_str()),
+                    };
                     let mut out = String::new();
-                    to_xml(
-                        successful
-                            .prop
-                            .get(name.split_once(':').map_or(name.as_str(), |name| name.1))?,
-                        &mut out,
-                    );
+                    to_xml(successful.get(namespace, name)?, &mut out);
                     Some(out)
                 }
             }
@@ -602,3 +1201,256 @@ fn parse_depth(value: &str) -> Result<Depth, ParseIntError> {
         Ok(Depth::Some(value.parse()?))
     }
 }
+
+fn parse_chunk_size(value: &str) -> Result<u64> {
+    let chunk_size = value.parse()?;
+    if chunk_size != 0 && chunk_size < ChunkedUpload::MIN_CHUNK_SIZE {
+        bail!(
+            "chunks must be at least {} bytes",
+            ChunkedUpload::MIN_CHUNK_SIZE
+        );
+    }
+    Ok(chunk_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use webdav_client::webdav_types::MultiStatus;
+
+    use super::*;
+
+    const URL: &str = "https://example.com/remote.php/dav/files/user/";
+
+    fn fields(fields: &str) -> Vec<ListField> {
+        fields
+            .split(',')
+            .map(parse_list_fields)
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    fn extract(fields: &[ListField], multi_status: &str) -> Vec<Vec<String>> {
+        let multi_status: MultiStatus = multi_status.parse().unwrap();
+        multi_status
+            .into_iter()
+            .map(|response| {
+                fields
+                    .iter()
+                    .map(|field| field.extract(&response, URL).unwrap_or_default())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Runs `webdav get` with `args` against a host nothing listens on.
+    fn get(args: &[&str]) -> Result<()> {
+        let args = ["webdav", "--host", "http://127.0.0.1:9/", "get"]
+            .iter()
+            .chain(args);
+        let Args {
+            login,
+            password,
+            host,
+            action:
+                Action::Get {
+                    path,
+                    out_path,
+                    resume,
+                    transfer,
+                },
+        } = Args::try_parse_from(args)?
+        else {
+            unreachable!()
+        };
+        Client::new(host, login, password).get(path, out_path, resume, &transfer)
+    }
+
+    #[test]
+    fn get_continue_requires_out_path() {
+        let error = get(&["--continue", "file"]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`--continue` requires `--out-path` or `--recursive`"
+        );
+        assert!(get(&["--continue", "--overwrite", "file"]).is_err());
+    }
+
+    #[test]
+    fn get_existing_fails_before_request() {
+        let path = env::temp_dir().join(format!("webdav-get-existing-{}", std::process::id()));
+        fs::write(&path, "local").unwrap();
+        let error = get(&["file", "--out-path", path.to_str().unwrap()]).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Could not create file for output"),
+            "{error:?}"
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "local");
+        fs::remove_file(&path).unwrap();
+
+        // The file is not left behind if the request fails.
+        assert!(get(&["file", "--out-path", path.to_str().unwrap()]).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn exit_codes() {
+        let code = |error| {
+            status_error(error, "/file")
+                .downcast::<ExitCodeError>()
+                .map(|ExitCodeError(code, error)| (code, error.to_string()))
+                .ok()
+        };
+        let status = |status| webdav_client::Error::ErrorStatus { status, text: None };
+        assert_eq!(code(status(404)), Some((44, "404 Not Found /file".into())));
+        assert_eq!(
+            code(status(409)),
+            Some((
+                49,
+                "409 Conflict (probably parent directory non-existent) /file".into()
+            ))
+        );
+        assert_eq!(code(status(423)), Some((43, "423 Locked /file".into())));
+        assert_eq!(
+            code(webdav_client::Error::PreconditionFailed { text: None }),
+            Some((
+                42,
+                "412 Precondition Failed (destination already exists) /file".into()
+            ))
+        );
+        assert_eq!(code(status(500)), None);
+        assert_eq!(code(webdav_client::Error::MixedConditions), None);
+
+        let multi_status: MultiStatus = r#"
+            <d:multistatus xmlns:d="DAV:">
+                <d:response>
+                    <d:href>/dav/dir/a</d:href>
+                    <d:status>HTTP/1.1 423 Locked</d:status>
+                </d:response>
+            </d:multistatus>
+        "#
+        .parse()
+        .unwrap();
+        let error = status_error(webdav_client::Error::MultiStatus(multi_status), "/dav/dir");
+        assert!(error.downcast_ref::<ExitCodeError>().is_none());
+        assert_eq!(
+            error.to_string(),
+            "Operation failed for some resources of /dav/dir:\n  /dav/dir/a 423 Locked"
+        );
+    }
+
+    #[test]
+    fn local_path_escape() {
+        let base = Path::new("out");
+        assert_eq!(
+            local_path(base, "a/b c").unwrap(),
+            base.join("a").join("b c")
+        );
+        assert!(local_path(base, "../a").is_err());
+        assert!(local_path(base, "a/../../b").is_err());
+        assert!(local_path(base, "/etc/passwd").is_err());
+    }
+
+    #[test]
+    fn chunk_size() {
+        assert_eq!(parse_chunk_size("0").unwrap(), 0);
+        assert_eq!(parse_chunk_size("5242880").unwrap(), 5_242_880);
+        assert!(parse_chunk_size("5242879").is_err());
+        assert!(parse_chunk_size("1").is_err());
+        assert!(parse_chunk_size("-1").is_err());
+    }
+
+    #[test]
+    fn custom_namespace_request() {
+        let fields = fields("{urn:a}color,{urn:b}color,{urn:a}shape,oc:fileid");
+        let (names, namespaces) = ListField::prop_find_fields(&fields).unwrap();
+        assert_eq!(names, [
+            "webdav-client-ns0:color",
+            "webdav-client-ns1:color",
+            "webdav-client-ns0:shape",
+            "oc:fileid"
+        ]);
+        for (prefix, uri) in [
+            ("webdav-client-ns0", "urn:a"),
+            ("webdav-client-ns1", "urn:b"),
+            ("oc", OWNCLOUD),
+        ] {
+            assert!(
+                namespaces.contains(&(prefix.to_owned(), uri.to_owned())),
+                "missing xmlns:{prefix}=\"{uri}\" in {namespaces:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn custom_namespace_extract() {
+        // The server is free to choose its own prefixes.
+        let multi_status = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:" xmlns:x="urn:a" xmlns:y="urn:b">
+              <d:response>
+                <d:href>/remote.php/dav/files/user/a.txt</d:href>
+                <d:propstat>
+                  <d:prop>
+                    <x:color>red</x:color>
+                    <y:color>blue</y:color>
+                    <shape xmlns="urn:a">round</shape>
+                  </d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/remote.php/dav/files/user/b%20c.txt</d:href>
+                <d:propstat>
+                  <d:prop>
+                    <y:color>green</y:color>
+                  </d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+                <d:propstat>
+                  <d:prop>
+                    <x:color/>
+                    <x:shape/>
+                  </d:prop>
+                  <d:status>HTTP/1.1 404 Not Found</d:status>
+                </d:propstat>
+              </d:response>
+            </d:multistatus>"#;
+        assert_eq!(
+            extract(
+                &fields("path,{urn:a}color,{urn:b}color,{urn:a}shape"),
+                multi_status
+            ),
+            [["a.txt", "red", "blue", "round"], [
+                "b c.txt", "", "green", ""
+            ]]
+        );
+    }
+
+    #[test]
+    fn predefined_namespace_extract() {
+        let multi_status = r#"<?xml version="1.0"?>
+            <multistatus xmlns="DAV:">
+              <response>
+                <href>/remote.php/dav/files/user/</href>
+                <propstat>
+                  <prop>
+                    <fileid xmlns="http://owncloud.org/ns">42</fileid>
+                    <fileid xmlns="urn:other">7</fileid>
+                    <getetag>"abc"</getetag>
+                  </prop>
+                  <status>HTTP/1.1 200 OK</status>
+                </propstat>
+              </response>
+            </multistatus>"#;
+        assert_eq!(
+            extract(
+                &fields(
+                    "path,oc:fileid,{http://owncloud.org/ns}fileid,{urn:other}fileid,d:getetag"
+                ),
+                multi_status
+            ),
+            [[".", "42", "42", "7", "\"abc\""]]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! In-memory [`WebClient`] for unit tests.
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+use super::*;
+
+#[derive(Debug, Clone)]
+/// Request received by a [`Mock`].
+pub(crate) struct Sent {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Sent {
+    /// Value of the request header `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Response returned by the handler of a [`Mock`].
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Adds the header `name`, can be called multiple times for the same
+    /// name.
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_owned(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+type Handler = dyn FnMut(&Sent) -> MockResponse + Send;
+
+#[derive(Clone)]
+/// Blocking web client answering every request with a handler and recording
+/// the requests.
+pub(crate) struct Mock {
+    handler: Arc<Mutex<Handler>>,
+    sent: Arc<Mutex<Vec<Sent>>>,
+}
+
+impl Mock {
+    pub fn client(handler: impl FnMut(&Sent) -> MockResponse + Send + 'static) -> Client<Self> {
+        Client::new(Self {
+            handler: Arc::new(Mutex::new(handler)),
+            sent: Arc::default(),
+        })
+    }
+
+    /// Requests sent so far.
+    pub fn sent(&self) -> Vec<Sent> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl WebClient for Mock {
+    type Asyncness = Blocking;
+    type Request = MockRequest;
+    type Response = MockResponse;
+
+    fn request(&self, method: &str, url: &str) -> MockRequest {
+        MockRequest {
+            mock: self.clone(),
+            sent: Sent {
+                method: method.to_owned(),
+                url: url.to_owned(),
+                headers: Vec::new(),
+                body: Vec::new(),
+            },
+        }
+    }
+}
+
+pub(crate) struct MockRequest {
+    mock: Mock,
+    sent: Sent,
+}
+
+impl Request for MockRequest {
+    type Asyncness = Blocking;
+    type Response = MockResponse;
+
+    fn header(mut self, key: &[u8], value: Vec<u8>) -> Self {
+        self.sent.headers.push((
+            String::from_utf8(key.to_vec()).unwrap(),
+            String::from_utf8(value).unwrap(),
+        ));
+        self
+    }
+
+    fn send(mut self, body: Option<Vec<u8>>) -> Result<MockResponse> {
+        self.sent.body = body.unwrap_or_default();
+        let response = (self.mock.handler.lock().unwrap())(&self.sent);
+        self.mock.sent.lock().unwrap().push(self.sent);
+        Ok(response)
+    }
+
+    fn send_stream(
+        self,
+        mut stream: Box<dyn Read + Send>,
+        _length: Option<u64>,
+    ) -> Result<MockResponse> {
+        let mut body = Vec::new();
+        stream.read_to_end(&mut body)?;
+        #[allow(deprecated)]
+        self.send(Some(body))
+    }
+}
+
+impl Response for MockResponse {
+    type Asyncness = Blocking;
+
+    fn bytes(self) -> Result<Vec<u8>> {
+        Ok(self.body)
+    }
+
+    fn stream(self) -> Box<dyn Read + Send> {
+        Box::new(Cursor::new(self.body))
+    }
+
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn header(&self, name: &str) -> Option<&[u8]> {
+        self.header_all(name).into_iter().next()
+    }
+
+    fn header_all(&self, name: &str) -> Vec<&[u8]> {
+        self.headers
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_bytes())
+            .collect()
+    }
+}
@@ -0,0 +1,177 @@
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display)]
+/// Compliance class advertised in the `DAV` header, see
+/// [`ServerCapabilities`].
+pub enum ComplianceClass {
+    /// Basic WebDAV support.
+    #[display("1")]
+    Class1,
+    /// Locking support, see [`Client::lock`].
+    #[display("2")]
+    Class2,
+    /// Support for the revisions of [RFC 4918](https://www.rfc-editor.org/rfc/rfc4918).
+    #[display("3")]
+    Class3,
+    /// WebDAV ACL ([RFC 3744](https://www.rfc-editor.org/rfc/rfc3744)).
+    #[display("access-control")]
+    AccessControl,
+    /// CalDAV ([RFC 4791](https://www.rfc-editor.org/rfc/rfc4791)).
+    #[display("calendar-access")]
+    CalendarAccess,
+    /// CardDAV ([RFC 6352](https://www.rfc-editor.org/rfc/rfc6352)).
+    #[display("addressbook")]
+    Addressbook,
+    /// Extended MKCOL ([RFC 5689](https://www.rfc-editor.org/rfc/rfc5689)).
+    #[display("extended-mkcol")]
+    ExtendedMkcol,
+    /// Versioning ([RFC 3253](https://www.rfc-editor.org/rfc/rfc3253)).
+    #[display("version-control")]
+    VersionControl,
+    /// Any other class, e.g., a coded URL like
+    /// `<http://apache.org/dav/propset/fs/1>`.
+    #[display("{_0}")]
+    Other(String),
+}
+
+impl From<&str> for ComplianceClass {
+    fn from(value: &str) -> Self {
+        match value {
+            "1" => Self::Class1,
+            "2" => Self::Class2,
+            "3" => Self::Class3,
+            "access-control" => Self::AccessControl,
+            "calendar-access" => Self::CalendarAccess,
+            "addressbook" => Self::Addressbook,
+            "extended-mkcol" => Self::ExtendedMkcol,
+            "version-control" => Self::VersionControl,
+            other => Self::Other(other.to_owned()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+/// Capabilities of a server returned by [`Client::options`].
+pub struct ServerCapabilities {
+    /// Compliance classes from the `DAV` header, empty for servers not
+    /// supporting WebDAV.
+    pub dav: Vec<ComplianceClass>,
+    /// Methods from the `Allow` header, in upper case.
+    pub allow: Vec<String>,
+    /// `MS-Author-Via` header, usually `DAV`.
+    pub ms_author_via: Option<String>,
+    /// Support for tus resumable uploads, see [`Client::tus_upload`].
+    pub tus: Option<TusCapabilities>,
+}
+
+impl ServerCapabilities {
+    /// Whether the server advertises compliance class `class`.
+    #[must_use]
+    pub fn supports(&self, class: &ComplianceClass) -> bool {
+        self.dav.contains(class)
+    }
+
+    /// Whether the server allows `method` (case-insensitive) on the resource.
+    #[must_use]
+    pub fn allows(&self, method: &str) -> bool {
+        self.allow
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method))
+    }
+
+    /// Whether the server supports WebDAV at all.
+    #[must_use]
+    pub fn is_webdav(&self) -> bool {
+        self.supports(&ComplianceClass::Class1)
+    }
+
+    /// Whether the server supports [`lock`](Client::lock) and
+    /// [`unlock`](Client::unlock).
+    #[must_use]
+    pub fn supports_locking(&self) -> bool {
+        self.supports(&ComplianceClass::Class2)
+    }
+
+    /// Whether the server supports versioning.
+    #[must_use]
+    pub fn supports_versioning(&self) -> bool {
+        self.supports(&ComplianceClass::VersionControl)
+    }
+
+    fn from_response(response: &impl Response) -> Self {
+        Self {
+            dav: header_list(response, "dav")
+                .map(ComplianceClass::from)
+                .collect(),
+            allow: header_list(response, "allow")
+                .map(str::to_ascii_uppercase)
+                .collect(),
+            ms_author_via: response
+                .header_str("ms-author-via")
+                .map(|value| value.trim().to_owned()),
+            tus: TusCapabilities::from_response(response),
+        }
+    }
+}
+
+/// Items of the comma separated header `name`, which can be sent multiple
+/// times, e.g., `DAV: 1, 2` and `DAV: <http://apache.org/dav/propset/fs/1>`.
+pub(crate) fn header_list<'a>(
+    response: &'a impl Response,
+    name: &str,
+) -> impl Iterator<Item = &'a str> {
+    response
+        .header_all(name)
+        .into_iter()
+        .filter_map(|value| str::from_utf8(value).ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+impl<T: WebClient<Asyncness = A>, A: Asyncness> Client<T> {
+    /// Discovers the capabilities of the server for the resource at `url`
+    /// using an `OPTIONS` request.
+    ///
+    /// Use it to check for e.g. locking support before relying on it.
+    pub fn options(&self, url: impl AsRef<str>) -> A::Future<Result<ServerCapabilities>> {
+        let response = self.request("OPTIONS", url.as_ref()).send_ok(None);
+        A::and_then(response, |response| {
+            Ok(ServerCapabilities::from_response(&response))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockResponse;
+
+    #[test]
+    fn repeated_headers() {
+        // Apache mod_dav sends its property set class in a separate header.
+        let response = MockResponse::new(200)
+            .header("DAV", "1,2")
+            .header("DAV", "<http://apache.org/dav/propset/fs/1>")
+            .header("Allow", "OPTIONS, propfind")
+            .header("Allow", "LOCK,,UNLOCK")
+            .header("MS-Author-Via", " DAV ");
+        let capabilities = ServerCapabilities::from_response(&response);
+        assert_eq!(capabilities.dav, [
+            ComplianceClass::Class1,
+            ComplianceClass::Class2,
+            ComplianceClass::Other("<http://apache.org/dav/propset/fs/1>".into()),
+        ]);
+        assert!(capabilities.supports_locking());
+        assert_eq!(capabilities.allow, [
+            "OPTIONS", "PROPFIND", "LOCK", "UNLOCK"
+        ]);
+        assert!(capabilities.allows("unlock"));
+        assert_eq!(capabilities.ms_author_via.as_deref(), Some("DAV"));
+        assert_eq!(capabilities.tus, None);
+
+        let capabilities = ServerCapabilities::from_response(&MockResponse::new(200));
+        assert!(!capabilities.is_webdav());
+        assert!(capabilities.allow.is_empty());
+    }
+}
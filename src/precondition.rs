@@ -0,0 +1,266 @@
+use std::fmt::Write;
+
+use super::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Condition of the WebDAV `If` header, see [`Precondition::conditions`].
+pub enum Condition {
+    /// Resource is locked by the lock with this token.
+    LockToken(String),
+    /// Resource has this ETag.
+    ETag(String),
+    /// Resource is not locked by the lock with this token.
+    NotLockToken(String),
+    /// Resource does not have this ETag.
+    NotETag(String),
+}
+
+#[derive(Debug, Clone, Default)]
+/// Preconditions of a request, sent as `If-Match`, `If-None-Match` and
+/// [`If`](https://www.rfc-editor.org/rfc/rfc4918#section-10.4) headers.
+///
+/// If they are not met, the server responds with
+/// [`Error::PreconditionFailed`].
+///
+/// Requests with both tagged and untagged `If` lists fail with
+/// [`Error::MixedConditions`] without being sent.
+///
+/// ```
+/// # use webdav_client::Precondition;
+/// // Only overwrite the version we downloaded before.
+/// let precondition = Precondition::new()
+///     .if_match(r#""5e4f8c""#)
+///     .lock_token("urn:uuid:e71d4fae-5dec-22d6-fea5-00a0c91e6be4");
+/// // Only create the resource if it does not exist yet.
+/// let precondition = Precondition::new().if_none_match_any();
+/// ```
+pub struct Precondition {
+    if_match: Vec<String>,
+    if_none_match: Vec<String>,
+    /// Condition lists of the `If` header with optional resource tag.
+    lists: Vec<(Option<String>, Vec<Condition>)>,
+    /// Tagged and untagged lists were added, see [`Error::MixedConditions`].
+    mixed: bool,
+}
+
+impl Precondition {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the resource to have `etag`, can be specified multiple times
+    /// to allow any of them.
+    ///
+    /// Unquoted ETags are quoted automatically.
+    #[must_use]
+    pub fn if_match(mut self, etag: impl AsRef<str>) -> Self {
+        self.if_match.push(entity_tag(etag.as_ref()));
+        self
+    }
+
+    /// Requires the resource to exist.
+    #[must_use]
+    pub fn if_match_any(mut self) -> Self {
+        self.if_match = vec!["*".to_owned()];
+        self
+    }
+
+    /// Requires the resource to not have `etag`, can be specified multiple
+    /// times to exclude all of them.
+    ///
+    /// For [`Client::get`] the server responds with `304 Not Modified`
+    /// instead of [`Error::PreconditionFailed`].
+    #[must_use]
+    pub fn if_none_match(mut self, etag: impl AsRef<str>) -> Self {
+        self.if_none_match.push(entity_tag(etag.as_ref()));
+        self
+    }
+
+    /// Requires the resource to not exist.
+    #[must_use]
+    pub fn if_none_match_any(mut self) -> Self {
+        self.if_none_match = vec!["*".to_owned()];
+        self
+    }
+
+    /// Submits the lock token, required to modify locked resources, see
+    /// [`Client::lock`].
+    #[must_use]
+    pub fn lock_token(self, token: impl Into<String>) -> Self {
+        self.conditions([Condition::LockToken(token.into())])
+    }
+
+    /// Adds a list of `conditions` to the `If` header that applies to the
+    /// resources affected by the request.
+    ///
+    /// The `If` header is met if all conditions of any list are met.
+    ///
+    /// Cannot be combined with
+    /// [`tagged_conditions`](Self::tagged_conditions), see
+    /// [`Error::MixedConditions`].
+    #[must_use]
+    pub fn conditions(self, conditions: impl IntoIterator<Item = Condition>) -> Self {
+        self.list(None, conditions)
+    }
+
+    /// Adds a list of `conditions` to the `If` header that applies to the
+    /// resource at `url`.
+    ///
+    /// Cannot be combined with [`conditions`](Self::conditions) or
+    /// [`lock_token`](Self::lock_token), see [`Error::MixedConditions`].
+    #[must_use]
+    pub fn tagged_conditions(
+        self,
+        url: impl Into<String>,
+        conditions: impl IntoIterator<Item = Condition>,
+    ) -> Self {
+        self.list(Some(url.into()), conditions)
+    }
+
+    fn list(
+        mut self,
+        url: Option<String>,
+        conditions: impl IntoIterator<Item = Condition>,
+    ) -> Self {
+        self.mixed |= self
+            .lists
+            .first()
+            .is_some_and(|(tag, _)| tag.is_some() != url.is_some());
+        self.lists.push((url, conditions.into_iter().collect()));
+        self
+    }
+
+    pub(crate) fn apply<R: Request>(&self, mut request: R) -> Result<R> {
+        if self.mixed {
+            return Err(Error::MixedConditions);
+        }
+        if !self.if_match.is_empty() {
+            request = request.header(b"if-match", self.if_match.join(", ").into_bytes());
+        }
+        if !self.if_none_match.is_empty() {
+            request = request.header(b"if-none-match", self.if_none_match.join(", ").into_bytes());
+        }
+        if !self.lists.is_empty() {
+            let mut header = String::new();
+            for (url, conditions) in &self.lists {
+                if !header.is_empty() {
+                    header.push(' ');
+                }
+                if let Some(url) = url {
+                    write!(header, "<{url}> ").unwrap();
+                }
+                header.push('(');
+                for (i, condition) in conditions.iter().enumerate() {
+                    if i > 0 {
+                        header.push(' ');
+                    }
+                    match condition {
+                        Condition::LockToken(token) => write!(header, "<{token}>"),
+                        Condition::ETag(etag) => write!(header, "[{}]", entity_tag(etag)),
+                        Condition::NotLockToken(token) => write!(header, "Not <{token}>"),
+                        Condition::NotETag(etag) => write!(header, "Not [{}]", entity_tag(etag)),
+                    }
+                    .unwrap();
+                }
+                header.push(')');
+            }
+            request = request.header(b"if", header.into_bytes());
+        }
+        Ok(request)
+    }
+}
+
+/// Quotes `etag` unless it is already quoted or a weak ETag.
+pub(crate) fn entity_tag(etag: &str) -> String {
+    if etag.starts_with('"') || etag.starts_with("W/") {
+        etag.to_owned()
+    } else {
+        format!("\"{etag}\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockResponse, Sent};
+
+    /// Request sent with `precondition`.
+    fn send(precondition: &Precondition) -> Sent {
+        let client = Mock::client(|_| MockResponse::new(204));
+        precondition
+            .apply(client.request("PUT", "http://example.com/file"))
+            .unwrap()
+            .send_ok(None)
+            .unwrap();
+        client.web_client.sent().remove(0)
+    }
+
+    #[test]
+    fn etags() {
+        let sent = send(
+            &Precondition::new()
+                .if_match("5e4f8c")
+                .if_match(r#""a1""#)
+                .if_none_match(r#"W/"b2""#),
+        );
+        assert_eq!(sent.header("if-match"), Some(r#""5e4f8c", "a1""#));
+        assert_eq!(sent.header("if-none-match"), Some(r#"W/"b2""#));
+        assert_eq!(sent.header("if"), None);
+
+        let sent = send(&Precondition::new().if_match_any().if_none_match_any());
+        assert_eq!(sent.header("if-match"), Some("*"));
+        assert_eq!(sent.header("if-none-match"), Some("*"));
+    }
+
+    #[test]
+    fn untagged() {
+        let sent = send(&Precondition::new().lock_token("urn:uuid:1").conditions([
+            Condition::NotLockToken("DAV:no-lock".into()),
+            Condition::ETag("a1".into()),
+            Condition::NotETag(r#"W/"b2""#.into()),
+        ]));
+        assert_eq!(
+            sent.header("if"),
+            Some(r#"(<urn:uuid:1>) (Not <DAV:no-lock> ["a1"] Not [W/"b2"])"#)
+        );
+        assert_eq!(sent.header("if-match"), None);
+    }
+
+    #[test]
+    fn tagged() {
+        let sent = send(
+            &Precondition::new()
+                .tagged_conditions("http://example.com/a", [
+                    Condition::LockToken("urn:uuid:1".into()),
+                    Condition::ETag("a1".into()),
+                ])
+                .tagged_conditions("http://example.com/b", [Condition::NotETag("b2".into())]),
+        );
+        assert_eq!(
+            sent.header("if"),
+            Some(
+                r#"<http://example.com/a> (<urn:uuid:1> ["a1"]) <http://example.com/b> (Not ["b2"])"#
+            )
+        );
+    }
+
+    #[test]
+    fn mixed() {
+        for precondition in [
+            Precondition::new()
+                .lock_token("urn:uuid:1")
+                .tagged_conditions("http://example.com/a", []),
+            Precondition::new()
+                .tagged_conditions("http://example.com/a", [])
+                .conditions([]),
+        ] {
+            let client = Mock::client(|_| MockResponse::new(204));
+            assert!(matches!(
+                client.put("http://example.com/file", Vec::new(), Some(&precondition)),
+                Err(Error::MixedConditions)
+            ));
+            assert!(client.web_client.sent().is_empty());
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::ops::{Bound, RangeBounds};
+use std::path::{Path, PathBuf};
+
+use super::*;
+
+/// Suffix of the file keeping the ETag of an incomplete download, see
+/// [`Client::download_resumable`].
+const ETAG_SUFFIX: &str = ".webdav-etag";
+
+/// Byte range of a `206 Partial Content` response, parsed from its
+/// `Content-Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// Offset of the first byte.
+    pub start: u64,
+    /// Offset of the last byte (inclusive).
+    pub end: u64,
+    /// Size of the complete resource, if known.
+    pub complete_length: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parses a `Content-Range` header like `bytes 0-499/1234`.
+    ///
+    /// Unsatisfied ranges (`bytes */1234`) are not accepted, as they are only
+    /// sent with `416 Range Not Satisfiable`.
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        let (unit, range) = value.trim().split_once(' ')?;
+        if !unit.eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+        let (range, complete_length) = range.trim().split_once('/')?;
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (start.parse().ok()?, end.parse().ok()?);
+        let complete_length = match complete_length {
+            "*" => None,
+            length => Some(length.parse().ok()?),
+        };
+        (start <= end && complete_length.is_none_or(|length| end < length)).then_some(Self {
+            start,
+            end,
+            complete_length,
+        })
+    }
+}
+
+/// Response to [`Client::get_range`].
+#[derive(Debug)]
+pub struct RangeResponse<R> {
+    pub response: R,
+    /// Range contained in the body, or [`None`] if the server sent the
+    /// complete resource, i.e., it ignored the `Range`, or the `If-Range`
+    /// condition was not met.
+    pub content_range: Option<ContentRange>,
+}
+
+impl<R> RangeResponse<R> {
+    /// Whether only the requested range was sent.
+    pub fn is_partial(&self) -> bool {
+        self.content_range.is_some()
+    }
+}
+
+impl<A: Asyncness, T: WebClient<Asyncness = A>> Client<T> {
+    /// Requests the bytes in `range` of the resource at `url`.
+    ///
+    /// With `if_range` set to an ETag, the server only sends the range if
+    /// the resource still has this ETag and the complete resource otherwise,
+    /// see [`RangeResponse::content_range`]. Servers not supporting ranges
+    /// send the complete resource as well.
+    ///
+    /// Ranges starting after the end of the resource fail with `416 Range Not
+    /// Satisfiable`.
+    ///
+    /// ```
+    /// # use webdav_client::{Client, Blocking};
+    /// # fn f(client: Client<impl webdav_client::WebClient<Asyncness = Blocking>>) -> webdav_client::Result<()> {
+    /// let response = client.get_range("file.img", 1024..2048, Some("\"etag\""))?;
+    /// if let Some(range) = response.content_range {
+    ///     println!("bytes {}-{}", range.start, range.end);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn get_range(
+        &self,
+        url: impl AsRef<str>,
+        range: impl RangeBounds<u64>,
+        if_range: Option<&str>,
+    ) -> A::Future<Result<RangeResponse<T::Response>>> {
+        let mut request = self
+            .request("GET", url.as_ref())
+            .header(b"range", range_header(&range).into_bytes());
+        if let Some(etag) = if_range {
+            request = request.header(b"if-range", entity_tag(etag).into_bytes());
+        }
+        A::and_then(request.send_ok(None), |response| {
+            let content_range = if response.status() == 206 {
+                let content_range = response
+                    .header_str("content-range")
+                    .and_then(ContentRange::parse)
+                    .ok_or_else(|| {
+                        Error::InvalidResponse(
+                            "206 Partial Content without valid Content-Range".into(),
+                        )
+                    })?;
+                Some(content_range)
+            } else {
+                None
+            };
+            Ok(RangeResponse {
+                response,
+                content_range,
+            })
+        })
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Client<T> {
+    /// Downloads the resource at `url` to the file at `path`, continuing the
+    /// partial file left behind by an interrupted download.
+    ///
+    /// While the download is incomplete, the strong ETag of the resource is
+    /// kept next to `path` (with the suffix `.webdav-etag`) and sent as
+    /// `If-Range` when continuing, so the download starts over if the
+    /// resource changed in between. Existing files without it, e.g., complete
+    /// downloads or resources with only a weak ETag, are downloaded again
+    /// completely, as there is no way to tell whether they are unchanged.
+    ///
+    /// Returns the number of bytes downloaded.
+    pub fn download_resumable(&self, url: impl AsRef<str>, path: impl AsRef<Path>) -> Result<u64> {
+        let (url, path) = (url.as_ref(), path.as_ref());
+        let etag_path = etag_path(path);
+        let offset = match fs::metadata(path) {
+            Ok(metadata) => metadata.len(),
+            Err(error) if error.kind() == ErrorKind::NotFound => 0,
+            Err(error) => return Err(error.into()),
+        };
+        if offset == 0 {
+            let response = self.get_raw(url, None)?;
+            return write_download(response, File::create(path)?, &etag_path);
+        }
+        let etag = match fs::read_to_string(&etag_path) {
+            Ok(etag) => Some(etag).filter(|etag| is_strong(etag)),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        let Some(etag) = etag else {
+            let response = self.get_raw(url, None)?;
+            return write_download(response, File::create(path)?, &etag_path);
+        };
+        // Requesting the last byte already downloaded as well avoids `416
+        // Range Not Satisfiable` for complete files and checks it is
+        // unchanged.
+        let response = match self.get_range(url, offset - 1.., Some(&etag)) {
+            Ok(RangeResponse {
+                response,
+                content_range: None,
+            }) => return write_download(response, File::create(path)?, &etag_path),
+            Ok(RangeResponse {
+                response,
+                content_range: Some(range),
+            }) if range.start == offset - 1 => response,
+            Ok(_) | Err(Error::ErrorStatus { status: 416, .. }) => {
+                let response = self.get_raw(url, None)?;
+                return write_download(response, File::create(path)?, &etag_path);
+            }
+            Err(error) => return Err(error),
+        };
+        let etag = response.header_str("etag").map(str::to_owned);
+        let mut stream = response.stream();
+        let mut file = File::options().read(true).append(true).open(path)?;
+        let (mut remote, mut local) = ([0], [0]);
+        stream.read_exact(&mut remote)?;
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut local)?;
+        if remote == local {
+            write_stream(etag.as_deref(), stream, file, &etag_path)
+        } else {
+            drop(stream);
+            let response = self.get_raw(url, None)?;
+            write_download(response, File::create(path)?, &etag_path)
+        }
+    }
+}
+
+/// Formats `range` as `Range` header.
+fn range_header(range: &impl RangeBounds<u64>) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    match range.end_bound() {
+        Bound::Included(end) => format!("bytes={start}-{end}"),
+        Bound::Excluded(end) => format!("bytes={start}-{}", end.saturating_sub(1)),
+        Bound::Unbounded => format!("bytes={start}-"),
+    }
+}
+
+/// Path of the file keeping the ETag of an incomplete download to `path`.
+fn etag_path(path: &Path) -> PathBuf {
+    let mut etag_path = path.as_os_str().to_owned();
+    etag_path.push(ETAG_SUFFIX);
+    etag_path.into()
+}
+
+fn write_download<R: web_client::Response<Asyncness = Blocking>>(
+    response: R,
+    file: File,
+    etag_path: &Path,
+) -> Result<u64> {
+    let etag = response.header_str("etag").map(str::to_owned);
+    write_stream(etag.as_deref(), response.stream(), file, etag_path)
+}
+
+/// Appends `stream` to `file`, keeping `etag` at `etag_path` until it is
+/// complete.
+fn write_stream(
+    etag: Option<&str>,
+    mut stream: impl Read,
+    mut file: File,
+    etag_path: &Path,
+) -> Result<u64> {
+    match etag.filter(|etag| is_strong(etag)) {
+        Some(etag) => fs::write(etag_path, etag)?,
+        None => remove_file(etag_path)?,
+    }
+    let written = io::copy(&mut stream, &mut file)?;
+    remove_file(etag_path)?;
+    Ok(written)
+}
+
+/// Whether `etag` is a strong ETag, i.e., usable in `If-Range`.
+fn is_strong(etag: &str) -> bool {
+    !etag.trim_start().starts_with("W/")
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    #[test]
+    fn content_range() {
+        for (value, expected) in [
+            ("bytes 0-499/1234", Some((0, 499, Some(1234)))),
+            ("bytes 500-999/*", Some((500, 999, None))),
+            ("Bytes 1233-1233/1234", Some((1233, 1233, Some(1234)))),
+            ("bytes */1234", None),
+            ("bytes 10-5/1234", None),
+            ("bytes 0-1234/1234", None),
+            ("items 0-1/2", None),
+        ] {
+            assert_eq!(
+                ContentRange::parse(value),
+                expected.map(|(start, end, complete_length)| ContentRange {
+                    start,
+                    end,
+                    complete_length
+                }),
+                "{value}"
+            );
+        }
+    }
+
+    /// Serves `content` with ETag `"2"`, honoring `Range` only if `If-Range`
+    /// matches.
+    fn serve(content: &'static [u8]) -> Client<Mock> {
+        Mock::client(move |sent| {
+            let range = sent
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.strip_suffix('-'))
+                .map(|start| start.parse::<usize>().unwrap());
+            match range {
+                Some(start) if sent.header("if-range") == Some("\"2\"") => MockResponse::new(206)
+                    .header(
+                        "Content-Range",
+                        format!("bytes {start}-{}/{}", content.len() - 1, content.len()),
+                    )
+                    .header("ETag", "\"2\"")
+                    .body(&content[start..]),
+                _ => MockResponse::new(200).header("ETag", "\"2\"").body(content),
+            }
+        })
+    }
+
+    #[test]
+    fn download_resumable() {
+        let dir = std::env::temp_dir().join(format!("webdav-download-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("file");
+        let url = "https://example.com/file";
+
+        // Continued with the strong ETag of the partial download.
+        fs::write(&path, "hello").unwrap();
+        fs::write(etag_path(&path), "\"2\"").unwrap();
+        let client = serve(b"hello world");
+        assert_eq!(client.download_resumable(url, &path).unwrap(), 6);
+        assert_eq!(fs::read(&path).unwrap(), b"hello world");
+        assert!(!etag_path(&path).exists());
+        let sent = client.web_client.sent();
+        assert_eq!(sent[0].header("range"), Some("bytes=4-"));
+        assert_eq!(sent[0].header("if-range"), Some("\"2\""));
+
+        // Without ETag the same prefix does not mean the file is unchanged.
+        fs::write(&path, "hello").unwrap();
+        let client = serve(b"hello there");
+        assert_eq!(client.download_resumable(url, &path).unwrap(), 11);
+        assert_eq!(fs::read(&path).unwrap(), b"hello there");
+        let sent = client.web_client.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].header("range"), None);
+
+        // Weak ETags cannot be used for `If-Range`.
+        fs::write(&path, "hello").unwrap();
+        fs::write(etag_path(&path), "W/\"2\"").unwrap();
+        let client = serve(b"hello again");
+        assert_eq!(client.download_resumable(url, &path).unwrap(), 11);
+        assert_eq!(fs::read(&path).unwrap(), b"hello again");
+        assert_eq!(client.web_client.sent()[0].header("range"), None);
+
+        // Stale ETag, the server sends the complete resource.
+        fs::write(&path, "hello").unwrap();
+        fs::write(etag_path(&path), "\"1\"").unwrap();
+        let client = serve(b"HELLO WORLD");
+        client.download_resumable(url, &path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"HELLO WORLD");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn range() {
+        assert_eq!(range_header(&(10..20)), "bytes=10-19");
+        assert_eq!(range_header(&(10..=20)), "bytes=10-20");
+        assert_eq!(range_header(&(10..)), "bytes=10-");
+        assert_eq!(range_header(&(..20)), "bytes=0-19");
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use super::*;
+
+const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Random access to a remote resource through ranged `GET` requests, see
+/// [`Client::open`].
+///
+/// Implements [`Read`] and [`Seek`] for [`Blocking`] clients. The resource
+/// is read in blocks of [`block_size`](Self::block_size), every request
+/// fetches up to [`read_ahead`](Self::read_ahead) following blocks as well
+/// and the most recently used [`cache_blocks`](Self::cache_blocks) blocks are
+/// kept in memory.
+///
+/// Its size and ETag are determined by the first request. If the server sent
+/// a strong ETag, it is sent as `If-Range` with every following request and
+/// reading fails with [`ErrorKind::InvalidData`] if the resource changed.
+/// Without a strong ETag, only changes of the size are detected, other
+/// changes can result in blocks of different versions being mixed.
+pub struct RemoteFile<'a, T> {
+    client: &'a Client<T>,
+    url: String,
+    block_size: usize,
+    read_ahead: usize,
+    cache: BlockCache,
+    position: u64,
+    /// Size of the resource, [`None`] before the first request.
+    len: Option<u64>,
+    /// Strong ETag of the resource sent as `If-Range`.
+    etag: Option<String>,
+}
+
+impl<T> Client<T> {
+    /// Opens the resource at `url` for random access without downloading it.
+    ///
+    /// No request is sent until the [`RemoteFile`] is read or seeked relative
+    /// to its end.
+    ///
+    /// ```no_run
+    /// # use std::io::{Read, Seek, SeekFrom};
+    /// # use webdav_client::{Blocking, Client, WebClient};
+    /// # fn open(client: &Client<impl WebClient<Asyncness = Blocking>>) -> std::io::Result<()> {
+    /// let mut file = client.open("backup.tar").block_size(1 << 20).read_ahead(4);
+    /// file.seek(SeekFrom::Start(512))?;
+    /// let mut header = [0; 512];
+    /// file.read_exact(&mut header)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn open(&self, url: impl AsRef<str>) -> RemoteFile<'_, T> {
+        RemoteFile {
+            client: self,
+            url: self.url(url),
+            block_size: DEFAULT_BLOCK_SIZE,
+            read_ahead: 1,
+            cache: BlockCache::new(16),
+            position: 0,
+            len: None,
+            etag: None,
+        }
+    }
+}
+
+impl<T> RemoteFile<'_, T> {
+    /// Size of the blocks requested and cached, defaults to `64 * 1024` bytes.
+    #[must_use]
+    pub fn block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self.cache.clear();
+        self
+    }
+
+    /// Number of blocks following a missing block requested along with it,
+    /// defaults to `1`.
+    #[must_use]
+    pub fn read_ahead(mut self, blocks: usize) -> Self {
+        self.read_ahead = blocks;
+        self
+    }
+
+    /// Number of blocks kept in memory, defaults to `16`.
+    #[must_use]
+    pub fn cache_blocks(mut self, blocks: usize) -> Self {
+        self.cache.capacity = blocks.max(1);
+        self
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> RemoteFile<'_, T> {
+    /// Size of the resource, requesting the first block if it is unknown.
+    pub fn size(&mut self) -> io::Result<u64> {
+        if let Some(len) = self.len {
+            return Ok(len);
+        }
+        self.fetch(0)?;
+        Ok(self.len.unwrap_or_default())
+    }
+
+    /// Returns the block `index`, requesting it if it is not cached.
+    fn block(&mut self, index: u64) -> io::Result<&[u8]> {
+        if !self.cache.touch(index) {
+            self.fetch(index)?;
+        }
+        Ok(self.cache.last())
+    }
+
+    /// Requests block `index` and up to [`read_ahead`](Self::read_ahead)
+    /// uncached blocks following it, leaving block `index` as the most
+    /// recently used.
+    fn fetch(&mut self, index: u64) -> io::Result<()> {
+        let block_size = self.block_size as u64;
+        let start = index * block_size;
+        let mut blocks: u64 = 1;
+        while blocks <= self.read_ahead as u64
+            && blocks < self.cache.capacity as u64
+            && self.len.is_none_or(|len| start + blocks * block_size < len)
+            && !self.cache.contains(index + blocks)
+        {
+            blocks += 1;
+        }
+        let mut end = start + blocks * block_size;
+        if let Some(len) = self.len {
+            end = end.min(len);
+        }
+        let RangeResponse {
+            response,
+            content_range,
+        } = match self
+            .client
+            .get_range(&self.url, start..end, self.etag.as_deref())
+        {
+            Ok(response) => response,
+            // Only the first block of an empty resource is unsatisfiable.
+            Err(Error::ErrorStatus { status: 416, .. }) if self.len.is_none() && start == 0 => {
+                self.len = Some(0);
+                self.cache.insert(0, Vec::new());
+                return Ok(());
+            }
+            Err(error) => return Err(io_error(error)),
+        };
+        let Some(range) = content_range else {
+            return Err(if self.len.is_some() {
+                changed()
+            } else {
+                io::Error::new(
+                    ErrorKind::Unsupported,
+                    "server does not support range requests",
+                )
+            });
+        };
+        match self.len {
+            Some(len) if range.complete_length.is_some_and(|length| length != len) => {
+                return Err(changed());
+            }
+            Some(_) => {}
+            None => {
+                self.len = Some(range.complete_length.ok_or_else(|| {
+                    io_error(Error::InvalidResponse(
+                        "Content-Range without complete length".into(),
+                    ))
+                })?);
+                self.etag = response
+                    .header_str("etag")
+                    .filter(|etag| !etag.starts_with("W/"))
+                    .map(str::to_owned);
+            }
+        }
+        if range.start != start {
+            return Err(io_error(Error::InvalidResponse(format!(
+                "requested range starting at {start}, got {}",
+                range.start
+            ))));
+        }
+        let mut data = Vec::new();
+        response.stream().read_to_end(&mut data)?;
+        if data.len() as u64 != range.end - range.start + 1 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        let mut blocks = data.chunks(self.block_size).map(<[u8]>::to_vec);
+        let first = blocks.next().unwrap_or_default();
+        for (block, data) in (index + 1..).zip(blocks) {
+            if !self.cache.contains(block) {
+                self.cache.insert(block, data);
+            }
+        }
+        self.cache.insert(index, first);
+        Ok(())
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Read for RemoteFile<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.size()? {
+            return Ok(0);
+        }
+        let block_size = self.block_size as u64;
+        let position = self.position;
+        let block = self.block(position / block_size)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = (position % block_size) as usize;
+        let read = buf.len().min(block.len().saturating_sub(offset));
+        buf[..read].copy_from_slice(&block[offset..offset + read]);
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Seek for RemoteFile<'_, T> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(offset) => self.size()?.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+fn changed() -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, "remote file changed")
+}
+
+fn io_error(error: Error) -> io::Error {
+    match error {
+        Error::Io(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+/// Least recently used blocks of a [`RemoteFile`].
+struct BlockCache {
+    capacity: usize,
+    /// Block index and data, most recently used last.
+    blocks: VecDeque<(u64, Vec<u8>)>,
+}
+
+impl BlockCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            blocks: VecDeque::new(),
+        }
+    }
+
+    fn contains(&self, index: u64) -> bool {
+        self.blocks.iter().any(|(block, _)| *block == index)
+    }
+
+    /// Marks block `index` as most recently used, returns whether it is
+    /// cached.
+    fn touch(&mut self, index: u64) -> bool {
+        let Some(position) = self.blocks.iter().position(|(block, _)| *block == index) else {
+            return false;
+        };
+        let block = self.blocks.remove(position).expect("position is valid");
+        self.blocks.push_back(block);
+        true
+    }
+
+    /// Most recently used block.
+    fn last(&self) -> &[u8] {
+        self.blocks.back().map_or(&[], |(_, data)| data)
+    }
+
+    fn insert(&mut self, index: u64, data: Vec<u8>) {
+        self.blocks.retain(|(block, _)| *block != index);
+        self.blocks.push_back((index, data));
+        while self.blocks.len() > self.capacity {
+            self.blocks.pop_front();
+        }
+    }
+
+    fn clear(&mut self) {
+        self.blocks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    const URL: &str = "https://example.com/file";
+
+    /// Content and ETag of a resource.
+    type Resource = Arc<Mutex<(&'static [u8], Option<&'static str>)>>;
+
+    /// Server answering ranged requests for `resource`, unless `If-Range`
+    /// does not match its ETag.
+    fn serve(resource: &Resource) -> Client<Mock> {
+        let resource = resource.clone();
+        Mock::client(move |sent| {
+            let (content, etag) = *resource.lock().unwrap();
+            let mut response = match sent
+                .header("range")
+                .and_then(|range| range.strip_prefix("bytes="))
+                .and_then(|range| range.split_once('-'))
+            {
+                Some((start, end))
+                    if sent.header("if-range").is_none_or(|tag| Some(tag) == etag) =>
+                {
+                    let start: usize = start.parse().unwrap();
+                    let end = end.parse::<usize>().unwrap().min(content.len() - 1);
+                    MockResponse::new(206)
+                        .header(
+                            "Content-Range",
+                            format!("bytes {start}-{end}/{}", content.len()),
+                        )
+                        .body(&content[start..=end])
+                }
+                _ => MockResponse::new(200).body(content),
+            };
+            if let Some(etag) = etag {
+                response = response.header("ETag", etag);
+            }
+            response
+        })
+    }
+
+    #[test]
+    fn read_and_seek() {
+        let resource: Resource = Arc::new(Mutex::new((b"0123456789abcdefghij", Some(r#""1""#))));
+        let client = serve(&resource);
+        let mut file = client.open(URL).block_size(4).read_ahead(1).cache_blocks(2);
+        let read = |file: &mut RemoteFile<_>, len| {
+            let mut buf = vec![0; len];
+            file.read_exact(&mut buf).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+
+        // Across three blocks, the first two are fetched with one request.
+        assert_eq!(file.seek(SeekFrom::Start(2)).unwrap(), 2);
+        assert_eq!(read(&mut file, 8), "23456789");
+        assert_eq!(file.seek(SeekFrom::End(-3)).unwrap(), 17);
+        let mut rest = String::new();
+        file.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "hij");
+        // Block 2 is still cached, block 3 was evicted.
+        assert_eq!(file.seek(SeekFrom::Current(-10)).unwrap(), 10);
+        assert_eq!(read(&mut file, 4), "abcd");
+        assert_eq!(file.seek(SeekFrom::Start(30)).unwrap(), 30);
+        assert_eq!(file.read(&mut [0; 4]).unwrap(), 0);
+        assert!(file.seek(SeekFrom::Current(-31)).is_err());
+
+        let sent = client.web_client.sent();
+        assert_eq!(
+            sent.iter()
+                .map(|sent| sent.header("range").unwrap())
+                .collect::<Vec<_>>(),
+            ["bytes=0-7", "bytes=8-15", "bytes=16-19", "bytes=12-15"]
+        );
+        assert_eq!(sent[0].header("if-range"), None);
+        for sent in &sent[1..] {
+            assert_eq!(sent.header("if-range"), Some(r#""1""#));
+        }
+    }
+
+    #[test]
+    fn change_detection() {
+        let resource: Resource = Arc::new(Mutex::new((b"0123456789", Some(r#""1""#))));
+        let client = serve(&resource);
+        let mut file = client.open(URL).block_size(4).read_ahead(0);
+        assert_eq!(file.read(&mut [0; 4]).unwrap(), 4);
+        *resource.lock().unwrap() = (b"9876543210", Some(r#""2""#));
+        let error = file.read(&mut [0; 4]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+        // Without ETag, only a changed size is detected.
+        let resource: Resource = Arc::new(Mutex::new((b"0123456789", None)));
+        let client = serve(&resource);
+        let mut file = client.open(URL).block_size(4).read_ahead(0);
+        assert_eq!(file.read(&mut [0; 4]).unwrap(), 4);
+        *resource.lock().unwrap() = (b"9876543210", None);
+        let mut buf = [0; 4];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"5432");
+        *resource.lock().unwrap() = (b"01234567890", None);
+        let error = file.read(&mut [0; 4]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn block_cache() {
+        let mut cache = BlockCache::new(2);
+        cache.insert(0, vec![0]);
+        cache.insert(1, vec![1]);
+        assert!(cache.touch(0));
+        cache.insert(2, vec![2]);
+        assert!(!cache.contains(1));
+        assert!(cache.touch(0));
+        assert_eq!(cache.last(), [0]);
+        assert!(!cache.touch(1));
+        cache.insert(2, vec![3]);
+        assert_eq!(cache.last(), [3]);
+        assert_eq!(cache.blocks.len(), 2);
+    }
+}
@@ -0,0 +1,1253 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write as _};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, utf8_percent_encode};
+use sha2::{Digest, Sha256};
+use time::OffsetDateTime;
+
+use super::*;
+
+/// Suffix of temporary files written during a sync, these are never
+/// synchronized.
+const TEMPORARY_SUFFIX: &str = ".webdav-sync-tmp";
+const STATE_HEADER: &str = "webdav-client sync state v1";
+/// Characters escaped in the fields of the state file.
+const FIELD: &AsciiSet = &CONTROLS.add(b'%');
+
+/// How to resolve files changed both locally and remotely since the last
+/// sync, see [`SyncOptions::conflict_policy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Keeps the remote version and renames the local one to a conflict
+    /// copy, which is uploaded as well.
+    #[default]
+    KeepBoth,
+    /// Replaces the remote version.
+    LocalWins,
+    /// Replaces the local version.
+    RemoteWins,
+}
+
+#[derive(Debug, Clone, Default)]
+/// Options for [`Client::sync_plan`].
+///
+/// ```
+/// # use webdav_client::{ConflictPolicy, SyncOptions};
+/// let options = SyncOptions::new()
+///     .conflict_policy(ConflictPolicy::LocalWins)
+///     .exclude("*.tmp")
+///     .exclude(".git");
+/// ```
+pub struct SyncOptions {
+    conflict_policy: ConflictPolicy,
+    exclude: Vec<String>,
+}
+
+impl SyncOptions {
+    /// Options keeping both versions on conflicts and excluding nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how to resolve conflicts.
+    #[must_use]
+    pub fn conflict_policy(mut self, conflict_policy: ConflictPolicy) -> Self {
+        self.conflict_policy = conflict_policy;
+        self
+    }
+
+    /// Ignores files and directories matching `glob` on both sides, see
+    /// [`Walk::include`] for the syntax.
+    #[must_use]
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+}
+
+/// Version of a resource after it was last synchronized, see
+/// [`SyncState`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncRecord {
+    /// Whether the resource is a collection (directory).
+    pub collection: bool,
+    /// ETag of the remote resource.
+    pub etag: Option<String>,
+    /// `DAV:getlastmodified` of the remote resource.
+    pub remote_modified: Option<OffsetDateTime>,
+    /// Size in bytes.
+    pub size: u64,
+    /// Modification time of the local file.
+    pub local_modified: Option<SystemTime>,
+    /// Hex encoded SHA-256 hash of the content.
+    pub hash: Option<String>,
+}
+
+impl SyncRecord {
+    fn parse(line: &str) -> Option<(String, Self)> {
+        let fields: Vec<_> = line
+            .split('\t')
+            .map(|field| percent_decode_str(field).decode_utf8().ok())
+            .collect::<Option<_>>()?;
+        let [
+            path,
+            kind,
+            etag,
+            remote_modified,
+            size,
+            local_modified,
+            hash,
+        ] = fields.as_slice()
+        else {
+            return None;
+        };
+        let optional = |field: &str| (!field.is_empty()).then(|| field.to_owned());
+        Some((path.to_string(), Self {
+            collection: match kind.as_ref() {
+                "d" => true,
+                "f" => false,
+                _ => return None,
+            },
+            etag: optional(etag),
+            remote_modified: match optional(remote_modified) {
+                Some(seconds) => {
+                    Some(OffsetDateTime::from_unix_timestamp(seconds.parse().ok()?).ok()?)
+                }
+                None => None,
+            },
+            size: size.parse().ok()?,
+            local_modified: match optional(local_modified) {
+                Some(nanos) => UNIX_EPOCH.checked_add(Duration::from_nanos(nanos.parse().ok()?)),
+                None => None,
+            },
+            hash: optional(hash),
+        }))
+    }
+
+    fn write(&self, out: &mut String, path: &str) {
+        let fields = [
+            path.to_owned(),
+            if self.collection { "d" } else { "f" }.to_owned(),
+            self.etag.clone().unwrap_or_default(),
+            self.remote_modified
+                .map(|modified| modified.unix_timestamp().to_string())
+                .unwrap_or_default(),
+            self.size.to_string(),
+            self.local_modified
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|modified| modified.as_nanos().to_string())
+                .unwrap_or_default(),
+            self.hash.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<_> = fields
+            .iter()
+            .map(|field| utf8_percent_encode(field, FIELD).to_string())
+            .collect();
+        out.push_str(&fields.join("\t"));
+        out.push('\n');
+    }
+}
+
+/// State of the last sync, used to tell deleted files apart from new ones.
+///
+/// Persist it between syncs using [`load`](Self::load) and
+/// [`save`](Self::save).
+#[derive(Debug, Clone, Default)]
+pub struct SyncState {
+    /// URL of the synchronized collection, a state is only valid for a single
+    /// collection.
+    pub remote: Option<String>,
+    /// Records by path relative to the synchronized directory.
+    pub records: BTreeMap<String, SyncRecord>,
+}
+
+impl SyncState {
+    /// Loads the state from the file at `path`, returns an empty state if it
+    /// does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let invalid = || io::Error::new(ErrorKind::InvalidData, "malformed sync state");
+        let file = match File::open(path) {
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            file => file?,
+        };
+        let mut lines = BufReader::new(file).lines();
+        if lines.next().transpose()?.as_deref() != Some(STATE_HEADER) {
+            return Err(invalid());
+        }
+        let remote = lines.next().transpose()?.ok_or_else(invalid)?;
+        let remote = percent_decode_str(&remote)
+            .decode_utf8()
+            .map_err(|_| invalid())?;
+        let records = lines
+            .map(|line| SyncRecord::parse(&line?).ok_or_else(invalid))
+            .collect::<io::Result<_>>()?;
+        Ok(Self {
+            remote: (!remote.is_empty()).then(|| remote.into_owned()),
+            records,
+        })
+    }
+
+    /// Saves the state to the file at `path`, replacing it atomically.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let mut content = format!(
+            "{STATE_HEADER}\n{}\n",
+            utf8_percent_encode(self.remote.as_deref().unwrap_or_default(), FIELD)
+        );
+        for (path, record) in &self.records {
+            record.write(&mut content, path);
+        }
+        let temporary = temporary_path(path);
+        fs::write(&temporary, content)?;
+        fs::rename(temporary, path)
+    }
+
+    /// Removes the records of `path` and its members.
+    fn forget(&mut self, path: &str) {
+        let members = format!("{path}/");
+        self.records
+            .retain(|record, _| record != path && !record.starts_with(&members));
+    }
+}
+
+/// Single change of a [`SyncPlan`], paths are relative to the synchronized
+/// directory.
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+pub enum SyncAction {
+    /// Create a local directory.
+    #[display("mkdir         {_0}")]
+    CreateLocal(String),
+    /// Create a remote collection, the synchronized collection itself if
+    /// empty.
+    #[display("mkcol         {}", if _0.is_empty() { "." } else { _0 })]
+    CreateRemote(String),
+    /// Upload a local file.
+    #[display("upload        {_0}")]
+    Upload(String),
+    /// Download a remote file.
+    #[display("download      {_0}")]
+    Download(String),
+    /// Delete a local file or directory, as it was deleted remotely.
+    ///
+    /// Directories still containing excluded files or symbolic links are
+    /// kept.
+    #[display("delete local  {_0}")]
+    DeleteLocal(String),
+    /// Delete a remote resource, as it was deleted locally.
+    ///
+    /// Collections still containing excluded resources are kept.
+    #[display("delete remote {_0}")]
+    DeleteRemote(String),
+    /// Download the remote file and rename the local file to
+    /// `conflict_path` and upload it, unless both are identical.
+    #[display("conflict      {path} (local copy as {conflict_path})")]
+    KeepBoth { path: String, conflict_path: String },
+    /// Skip a path that is a file on one side and a directory on the other.
+    #[display("skip          {_0} (file and directory)")]
+    TypeMismatch(String),
+}
+
+#[derive(Debug, Clone)]
+struct LocalEntry {
+    collection: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone)]
+struct RemoteEntry {
+    collection: bool,
+    url: String,
+    etag: Option<String>,
+    modified: Option<OffsetDateTime>,
+    size: u64,
+}
+
+/// Changes needed to synchronize a local directory and a remote collection,
+/// see [`Client::sync_plan`].
+#[derive(Debug, Clone)]
+pub struct SyncPlan {
+    /// Changes in the order they are applied.
+    pub actions: Vec<SyncAction>,
+    local_root: PathBuf,
+    remote_root: String,
+    local: BTreeMap<String, LocalEntry>,
+    remote: BTreeMap<String, RemoteEntry>,
+    /// Records of resources without changes, `None` to remove them.
+    updates: Vec<(String, Option<SyncRecord>)>,
+}
+
+impl SyncPlan {
+    /// Whether both sides are already in sync.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    fn local_path(&self, path: &str) -> PathBuf {
+        path.split('/')
+            .fold(self.local_root.clone(), |local, segment| {
+                local.join(segment)
+            })
+    }
+
+    fn remote_url(&self, path: &str) -> String {
+        match self.remote.get(path) {
+            Some(remote) => remote.url.clone(),
+            None => url::resolve(&self.remote_root, &url::escape_path(path)),
+        }
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Client<T> {
+    /// Compares the local directory `local` with the collection at `remote`
+    /// and plans the changes to synchronize them in both directions.
+    ///
+    /// Local changes are detected using size, modification time and hash,
+    /// remote changes using ETag, `DAV:getlastmodified` and size, both
+    /// compared to `state`. Nothing is modified, so the plan can be used for a
+    /// dry run, see [`sync`](Self::sync) to apply it.
+    ///
+    /// Local symbolic links are ignored.
+    ///
+    /// ```no_run
+    /// # use webdav_client::{Blocking, Client, SyncOptions, SyncState, WebClient};
+    /// # fn sync(client: &Client<impl WebClient<Asyncness = Blocking>>) -> webdav_client::Result<()> {
+    /// let mut state = SyncState::load("Notes/.sync-state")?;
+    /// let options = SyncOptions::new().exclude(".sync-state");
+    /// let plan = client.sync_plan("Notes", "https://example.com/dav/Notes", &state, &options)?;
+    /// let result = client.sync(&plan, &mut state);
+    /// state.save("Notes/.sync-state")?;
+    /// result
+    /// # }
+    /// ```
+    pub fn sync_plan(
+        &self,
+        local: impl AsRef<Path>,
+        remote: impl AsRef<str>,
+        state: &SyncState,
+        options: &SyncOptions,
+    ) -> Result<SyncPlan> {
+        let mut remote_root = self.url(remote);
+        if !remote_root.ends_with('/') {
+            remote_root.push('/');
+        }
+        if let Some(state_remote) = &state.remote
+            && *state_remote != remote_root
+        {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("sync state belongs to `{state_remote}`"),
+            )
+            .into());
+        }
+        let local_root = local.as_ref().to_owned();
+        let local = scan_local(&local_root, |path| {
+            path.ends_with(TEMPORARY_SUFFIX)
+                || options.exclude.iter().any(|glob| glob_match(glob, path))
+        })?;
+
+        let mut walk = self
+            .walk(&remote_root)
+            .exclude(format!("*{TEMPORARY_SUFFIX}"));
+        for glob in &options.exclude {
+            walk = walk.exclude(glob);
+        }
+        let mut remote = BTreeMap::new();
+        let mut remote_exists = true;
+        for entry in walk {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) if e.is_not_found() && remote.is_empty() => {
+                    remote_exists = false;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if entry
+                .path
+                .split('/')
+                .any(|segment| matches!(segment, "" | "." | ".."))
+            {
+                continue;
+            }
+            remote.insert(entry.path, RemoteEntry {
+                collection: entry.properties.is_collection(),
+                url: entry.url,
+                etag: entry.properties.etag,
+                modified: entry.properties.last_modified,
+                size: entry.properties.content_length.unwrap_or_default(),
+            });
+        }
+
+        let mut plan = decide(
+            SyncPlan {
+                actions: Vec::new(),
+                local_root,
+                remote_root,
+                local,
+                remote,
+                updates: Vec::new(),
+            },
+            state,
+            options.conflict_policy,
+        )?;
+        if !remote_exists {
+            plan.actions
+                .insert(0, SyncAction::CreateRemote(String::new()));
+        }
+        Ok(plan)
+    }
+
+    /// Applies `plan` created by [`sync_plan`](Self::sync_plan) and records
+    /// the synchronized versions in `state`.
+    ///
+    /// Stops at the first error, `state` contains all changes applied until
+    /// then and should be saved either way.
+    pub fn sync(&self, plan: &SyncPlan, state: &mut SyncState) -> Result<()> {
+        fs::create_dir_all(&plan.local_root)?;
+        state.remote = Some(plan.remote_root.clone());
+        for (path, update) in &plan.updates {
+            match update {
+                Some(record) => state.records.insert(path.clone(), record.clone()),
+                None => state.records.remove(path),
+            };
+        }
+        for action in &plan.actions {
+            self.apply(plan, action, state)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn apply(&self, plan: &SyncPlan, action: &SyncAction, state: &mut SyncState) -> Result<()> {
+        match action {
+            SyncAction::CreateLocal(path) => {
+                fs::create_dir_all(plan.local_path(path))?;
+                let remote = plan.remote.get(path);
+                state.records.insert(path.clone(), SyncRecord {
+                    collection: true,
+                    etag: remote.and_then(|remote| remote.etag.clone()),
+                    remote_modified: remote.and_then(|remote| remote.modified),
+                    ..SyncRecord::default()
+                });
+            }
+            SyncAction::CreateRemote(path) => {
+                match self.mkcol(plan.remote_url(path), None) {
+                    // 405 Method Not Allowed is returned for existing collections
+                    Ok(()) | Err(Error::ErrorStatus { status: 405, .. }) => {}
+                    Err(e) => return Err(e),
+                }
+                if !path.is_empty() {
+                    state.records.insert(path.clone(), SyncRecord {
+                        collection: true,
+                        ..SyncRecord::default()
+                    });
+                }
+            }
+            SyncAction::Upload(path) => {
+                let precondition = match plan.remote.get(path) {
+                    Some(remote) => remote
+                        .etag
+                        .as_ref()
+                        .map(|etag| Precondition::new().if_match(etag)),
+                    None => Some(Precondition::new().if_none_match_any()),
+                };
+                let record = self.upload_file(
+                    &plan.local_path(path),
+                    &plan.remote_url(path),
+                    precondition.as_ref(),
+                )?;
+                state.records.insert(path.clone(), record);
+            }
+            SyncAction::Download(path) => {
+                let local_path = plan.local_path(path);
+                let temporary = temporary_path(&local_path);
+                let remote = &plan.remote[path];
+                let (etag, hash) = self.download_file(remote, &temporary)?;
+                // Do not overwrite changes made after planning.
+                if let Some(local) = plan.local.get(path) {
+                    let metadata = fs::metadata(&local_path)?;
+                    if metadata.len() != local.size || metadata.modified().ok() != local.modified {
+                        fs::remove_file(&temporary)?;
+                        return Err(io::Error::other(format!(
+                            "`{}` was modified during sync",
+                            local_path.display()
+                        ))
+                        .into());
+                    }
+                }
+                fs::rename(&temporary, &local_path)?;
+                let record = local_record(&local_path, etag, remote.modified, hash)?;
+                state.records.insert(path.clone(), record);
+            }
+            SyncAction::DeleteLocal(path) => {
+                let local_path = plan.local_path(path);
+                match plan.local.get(path) {
+                    // Members in the plan were deleted before, others are kept.
+                    Some(local) if local.collection => match fs::remove_dir(local_path) {
+                        Err(e) if e.kind() == ErrorKind::DirectoryNotEmpty => {}
+                        result => result?,
+                    },
+                    _ => fs::remove_file(local_path)?,
+                }
+                state.forget(path);
+            }
+            SyncAction::DeleteRemote(path) => {
+                let remote = &plan.remote[path];
+                // Members in the plan were deleted before, others are kept.
+                if !(remote.collection && self.has_members(&remote.url)?) {
+                    let precondition = remote
+                        .etag
+                        .as_ref()
+                        .filter(|_| !remote.collection)
+                        .map(|etag| Precondition::new().if_match(etag));
+                    match self.delete(&remote.url, precondition.as_ref()) {
+                        Ok(()) => {}
+                        Err(e) if e.is_not_found() => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                state.forget(path);
+            }
+            SyncAction::KeepBoth {
+                path,
+                conflict_path,
+            } => {
+                let local_path = plan.local_path(path);
+                let temporary = temporary_path(&local_path);
+                let remote = &plan.remote[path];
+                let (etag, hash) = self.download_file(remote, &temporary)?;
+                if hash_file(&local_path)? != hash {
+                    let conflict = plan.local_path(conflict_path);
+                    fs::rename(&local_path, &conflict)?;
+                    let record = self.upload_file(
+                        &conflict,
+                        &plan.remote_url(conflict_path),
+                        Some(&Precondition::new().if_none_match_any()),
+                    )?;
+                    state.records.insert(conflict_path.clone(), record);
+                }
+                fs::rename(&temporary, &local_path)?;
+                let record = local_record(&local_path, etag, remote.modified, hash)?;
+                state.records.insert(path.clone(), record);
+            }
+            SyncAction::TypeMismatch(_) => {}
+        }
+        Ok(())
+    }
+
+    fn upload_file(
+        &self,
+        local_path: &Path,
+        url: &str,
+        precondition: Option<&Precondition>,
+    ) -> Result<SyncRecord> {
+        let hash = hash_file(local_path)?;
+        let file = File::open(local_path)?;
+        let metadata = file.metadata()?;
+        let mut etag = self.put(url, Body::reader(file, Some(metadata.len())), precondition)?;
+        let mut remote_modified = None;
+        if etag.is_none() {
+            let properties = [
+                QualifiedName::dav("getetag"),
+                QualifiedName::dav("getlastmodified"),
+            ];
+            let multi_status =
+                self.prop_find_with(url, Depth::Some(0), &PropFind::Prop(properties.into()))?;
+            if let Some(response) = multi_status.responses.first() {
+                etag = response.etag()?;
+                remote_modified = response.last_modified()?;
+            }
+        }
+        Ok(SyncRecord {
+            collection: false,
+            etag,
+            remote_modified,
+            size: metadata.len(),
+            local_modified: metadata.modified().ok(),
+            hash: Some(hash),
+        })
+    }
+
+    /// Whether the collection at `url` has any members.
+    fn has_members(&self, url: &str) -> Result<bool> {
+        let prop_find = PropFind::Prop(vec![QualifiedName::dav("resourcetype")]);
+        match self.prop_find_with(url, Depth::Some(1), &prop_find) {
+            Ok(multi_status) => Ok(multi_status.responses.iter().any(|response| {
+                response
+                    .relative_path(url)
+                    .is_some_and(|path| !path.is_empty())
+            })),
+            Err(e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Downloads `remote` to `temporary`, returns the ETag and hash.
+    fn download_file(
+        &self,
+        remote: &RemoteEntry,
+        temporary: &Path,
+    ) -> Result<(Option<String>, String)> {
+        let response = self.get_raw(&remote.url, None)?;
+        let etag = response
+            .header_str("etag")
+            .map(str::to_owned)
+            .or_else(|| remote.etag.clone());
+        let mut file = File::create(temporary)?;
+        let mut hasher = Sha256::new();
+        let mut stream = response.stream();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            let read = stream.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            file.write_all(&buffer[..read])?;
+        }
+        if let Some(modified) = remote.modified {
+            file.set_modified(modified.into())?;
+        }
+        Ok((etag, util::hex(&hasher.finalize())))
+    }
+}
+
+/// Decides the actions for every path of `plan`'s local and remote entries.
+fn decide(mut plan: SyncPlan, state: &SyncState, policy: ConflictPolicy) -> io::Result<SyncPlan> {
+    let mut paths: Vec<_> = plan
+        .local
+        .keys()
+        .chain(plan.remote.keys())
+        .chain(state.records.keys())
+        .cloned()
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let mut actions = BTreeMap::new();
+    let mut mismatched: Vec<String> = Vec::new();
+    for path in paths {
+        if mismatched
+            .iter()
+            .any(|mismatched| path.starts_with(&format!("{mismatched}/")))
+        {
+            continue;
+        }
+        let local = plan.local.get(&path);
+        let remote = plan.remote.get(&path);
+        let action = match (local, remote, state.records.get(&path)) {
+            (Some(local), Some(remote), _) if local.collection != remote.collection => {
+                mismatched.push(path.clone());
+                Some(SyncAction::TypeMismatch(path.clone()))
+            }
+            (Some(local), Some(remote), Some(record)) => {
+                let mut record = record.clone();
+                let local_changed = local_changed(&plan, &path, local, &mut record)?;
+                match (local_changed, remote_changed(&record, remote)) {
+                    (false, false) => {
+                        record.etag.clone_from(&remote.etag);
+                        record.remote_modified = remote.modified;
+                        plan.updates.push((path.clone(), Some(record)));
+                        None
+                    }
+                    (true, false) => Some(SyncAction::Upload(path.clone())),
+                    (false, true) => Some(SyncAction::Download(path.clone())),
+                    (true, true) => Some(conflict(&plan, &path, policy)),
+                }
+            }
+            (Some(_), Some(remote), None) if remote.collection => {
+                plan.updates.push((
+                    path.clone(),
+                    Some(SyncRecord {
+                        collection: true,
+                        etag: remote.etag.clone(),
+                        remote_modified: remote.modified,
+                        ..SyncRecord::default()
+                    }),
+                ));
+                None
+            }
+            (Some(_), Some(_), None) => Some(conflict(&plan, &path, policy)),
+            (Some(local), None, Some(record)) => {
+                if local_changed(&plan, &path, local, &mut record.clone())? {
+                    Some(upload(&path, local))
+                } else {
+                    Some(SyncAction::DeleteLocal(path.clone()))
+                }
+            }
+            (None, Some(remote), Some(record)) => {
+                if remote_changed(record, remote) {
+                    Some(download(&path, remote))
+                } else {
+                    Some(SyncAction::DeleteRemote(path.clone()))
+                }
+            }
+            (Some(local), None, None) => Some(upload(&path, local)),
+            (None, Some(remote), None) => Some(download(&path, remote)),
+            (None, None, _) => {
+                plan.updates.push((path.clone(), None));
+                None
+            }
+        };
+        if let Some(action) = action {
+            actions.insert(path, action);
+        }
+    }
+
+    plan.actions = order(&plan, actions);
+    Ok(plan)
+}
+
+/// Resolves deleted directories and orders `actions` to be applied.
+fn order(plan: &SyncPlan, mut actions: BTreeMap<String, SyncAction>) -> Vec<SyncAction> {
+    // A deleted directory is recreated if any of its members changed,
+    // otherwise its members are deleted before it.
+    let deleted: Vec<_> = actions
+        .iter()
+        .filter(|(path, action)| {
+            matches!(action, SyncAction::DeleteLocal(_)) && plan.local[*path].collection
+                || matches!(action, SyncAction::DeleteRemote(_)) && plan.remote[*path].collection
+        })
+        .map(|(path, _)| path.clone())
+        .collect();
+    for path in deleted {
+        let members = format!("{path}/");
+        let changed = actions
+            .range(members.clone()..)
+            .take_while(|(member, _)| member.starts_with(&members))
+            .any(|(_, action)| {
+                !matches!(
+                    action,
+                    SyncAction::DeleteLocal(_) | SyncAction::DeleteRemote(_)
+                )
+            });
+        if changed {
+            let action = match actions[&path] {
+                SyncAction::DeleteLocal(_) => SyncAction::CreateRemote(path.clone()),
+                _ => SyncAction::CreateLocal(path.clone()),
+            };
+            actions.insert(path, action);
+        }
+    }
+
+    let rank = |action: &SyncAction| match action {
+        SyncAction::CreateLocal(_) | SyncAction::CreateRemote(_) => 0,
+        SyncAction::DeleteLocal(_) | SyncAction::DeleteRemote(_) => 2,
+        _ => 1,
+    };
+    let mut actions: Vec<_> = actions.into_iter().collect();
+    // Parents are created before and deleted after their members.
+    actions.sort_by(|(a_path, a), (b_path, b)| {
+        rank(a).cmp(&rank(b)).then_with(|| {
+            if rank(a) == 2 {
+                b_path.cmp(a_path)
+            } else {
+                a_path.cmp(b_path)
+            }
+        })
+    });
+    actions.into_iter().map(|(_, action)| action).collect()
+}
+
+fn upload(path: &str, local: &LocalEntry) -> SyncAction {
+    if local.collection {
+        SyncAction::CreateRemote(path.to_owned())
+    } else {
+        SyncAction::Upload(path.to_owned())
+    }
+}
+
+fn download(path: &str, remote: &RemoteEntry) -> SyncAction {
+    if remote.collection {
+        SyncAction::CreateLocal(path.to_owned())
+    } else {
+        SyncAction::Download(path.to_owned())
+    }
+}
+
+fn conflict(plan: &SyncPlan, path: &str, policy: ConflictPolicy) -> SyncAction {
+    match policy {
+        ConflictPolicy::LocalWins => SyncAction::Upload(path.to_owned()),
+        ConflictPolicy::RemoteWins => SyncAction::Download(path.to_owned()),
+        ConflictPolicy::KeepBoth => {
+            let (directory, name) = path
+                .rsplit_once('/')
+                .map_or(("", path), |(directory, name)| {
+                    (&path[..=directory.len()], name)
+                });
+            let (stem, extension) = match name.rsplit_once('.') {
+                Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+                _ => (name, String::new()),
+            };
+            let mut conflict_path = format!("{directory}{stem} (conflict){extension}");
+            let mut n = 1;
+            while plan.local.contains_key(&conflict_path)
+                || plan.remote.contains_key(&conflict_path)
+            {
+                n += 1;
+                conflict_path = format!("{directory}{stem} (conflict {n}){extension}");
+            }
+            SyncAction::KeepBoth {
+                path: path.to_owned(),
+                conflict_path,
+            }
+        }
+    }
+}
+
+/// Whether the local file changed since `record`, updates the modification
+/// time of `record` if only that changed.
+fn local_changed(
+    plan: &SyncPlan,
+    path: &str,
+    local: &LocalEntry,
+    record: &mut SyncRecord,
+) -> io::Result<bool> {
+    if local.collection != record.collection {
+        return Ok(true);
+    }
+    if local.collection {
+        return Ok(false);
+    }
+    if local.size != record.size {
+        return Ok(true);
+    }
+    if local.modified == record.local_modified {
+        return Ok(false);
+    }
+    if record.hash.is_none() {
+        return Ok(true);
+    }
+    if record.hash.as_deref() == Some(&hash_file(&plan.local_path(path))?) {
+        record.local_modified = local.modified;
+        Ok(false)
+    } else {
+        Ok(true)
+    }
+}
+
+/// Whether the remote resource changed since `record`.
+fn remote_changed(record: &SyncRecord, remote: &RemoteEntry) -> bool {
+    if remote.collection != record.collection {
+        return true;
+    }
+    if remote.collection {
+        return false;
+    }
+    match (
+        &record.etag,
+        &remote.etag,
+        record.remote_modified,
+        remote.modified,
+    ) {
+        (Some(before), Some(after), ..) => before != after,
+        (.., Some(before), Some(after)) => before != after,
+        _ => record.size != remote.size,
+    }
+}
+
+fn scan_local(
+    root: &Path,
+    excluded: impl Fn(&str) -> bool,
+) -> io::Result<BTreeMap<String, LocalEntry>> {
+    let mut entries = BTreeMap::new();
+    let mut directories = vec![(root.to_owned(), String::new())];
+    while let Some((directory, relative)) = directories.pop() {
+        let read_dir = match fs::read_dir(&directory) {
+            Err(e) if e.kind() == ErrorKind::NotFound && relative.is_empty() => continue,
+            read_dir => read_dir?,
+        };
+        for entry in read_dir {
+            let entry = entry?;
+            let name = entry.file_name().into_string().map_err(|name| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("file name is not valid UTF-8 {}", name.display()),
+                )
+            })?;
+            let path = if relative.is_empty() {
+                name
+            } else {
+                format!("{relative}/{name}")
+            };
+            if excluded(&path) {
+                continue;
+            }
+            // Symbolic links are not synchronized, following them could loop.
+            let metadata = fs::symlink_metadata(entry.path())?;
+            if metadata.is_symlink() {
+                continue;
+            }
+            if metadata.is_dir() {
+                directories.push((entry.path(), path.clone()));
+            }
+            entries.insert(path, LocalEntry {
+                collection: metadata.is_dir(),
+                size: if metadata.is_dir() { 0 } else { metadata.len() },
+                modified: metadata.modified().ok(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn local_record(
+    local_path: &Path,
+    etag: Option<String>,
+    remote_modified: Option<OffsetDateTime>,
+    hash: String,
+) -> io::Result<SyncRecord> {
+    let metadata = fs::metadata(local_path)?;
+    Ok(SyncRecord {
+        collection: false,
+        etag,
+        remote_modified,
+        size: metadata.len(),
+        local_modified: metadata.modified().ok(),
+        hash: Some(hash),
+    })
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(TEMPORARY_SUFFIX);
+    path.with_file_name(name)
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(util::hex(&hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{Mock, MockResponse};
+
+    /// `207 Multi-Status` listing `paths` below `/dav/`, collections end with
+    /// `/`.
+    fn listing(paths: &[&str]) -> MockResponse {
+        let mut body = String::from(r#"<d:multistatus xmlns:d="DAV:">"#);
+        for path in paths {
+            let resource_type = if path.is_empty() || path.ends_with('/') {
+                "<d:collection/>"
+            } else {
+                ""
+            };
+            write!(
+                body,
+                r"<d:response>
+                    <d:href>/dav/{path}</d:href>
+                    <d:propstat>
+                        <d:prop>
+                            <d:resourcetype>{resource_type}</d:resourcetype>
+                            <d:getetag>&quot;1&quot;</d:getetag>
+                            <d:getcontentlength>1</d:getcontentlength>
+                        </d:prop>
+                        <d:status>HTTP/1.1 200 OK</d:status>
+                    </d:propstat>
+                </d:response>"
+            )
+            .unwrap();
+        }
+        body.push_str("</d:multistatus>");
+        MockResponse::new(207).body(body)
+    }
+
+    /// State after syncing `dir` containing `dir/file`, modified at
+    /// `modified`.
+    fn synced_dir(modified: Option<SystemTime>) -> SyncState {
+        SyncState {
+            remote: Some("https://example.com/dav/".to_owned()),
+            records: [
+                ("dir".to_owned(), SyncRecord {
+                    collection: true,
+                    ..SyncRecord::default()
+                }),
+                ("dir/file".to_owned(), SyncRecord {
+                    etag: Some(r#""1""#.to_owned()),
+                    size: 1,
+                    local_modified: modified,
+                    ..SyncRecord::default()
+                }),
+            ]
+            .into(),
+        }
+    }
+
+    fn plan_for(
+        local: &[(&str, bool, u64)],
+        remote: &[(&str, bool, &str)],
+        records: &[(&str, bool, &str, u64)],
+        policy: ConflictPolicy,
+    ) -> Vec<String> {
+        let modified = Some(UNIX_EPOCH);
+        let state = SyncState {
+            remote: None,
+            records: records
+                .iter()
+                .map(|&(path, collection, etag, size)| {
+                    (path.to_owned(), SyncRecord {
+                        collection,
+                        etag: Some(etag.to_owned()),
+                        size,
+                        local_modified: modified,
+                        ..SyncRecord::default()
+                    })
+                })
+                .collect(),
+        };
+        let plan = SyncPlan {
+            actions: Vec::new(),
+            local_root: PathBuf::from("local"),
+            remote_root: "https://example.com/dav/".to_owned(),
+            local: local
+                .iter()
+                .map(|&(path, collection, size)| {
+                    (path.to_owned(), LocalEntry {
+                        collection,
+                        size,
+                        modified,
+                    })
+                })
+                .collect(),
+            remote: remote
+                .iter()
+                .map(|&(path, collection, etag)| {
+                    (path.to_owned(), RemoteEntry {
+                        collection,
+                        url: format!("https://example.com/dav/{path}"),
+                        etag: Some(etag.to_owned()),
+                        modified: None,
+                        size: 0,
+                    })
+                })
+                .collect(),
+            updates: Vec::new(),
+        };
+        decide(plan, &state, policy)
+            .unwrap()
+            .actions
+            .iter()
+            .map(ToString::to_string)
+            .map(|action| action.split_whitespace().collect::<Vec<_>>().join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn new_and_deleted() {
+        assert_eq!(
+            plan_for(
+                &[
+                    ("new.txt", false, 1),
+                    ("kept.txt", false, 1),
+                    ("dir", true, 0)
+                ],
+                &[
+                    ("kept.txt", false, "a"),
+                    ("gone.txt", false, "b"),
+                    ("remote", true, "")
+                ],
+                &[
+                    ("kept.txt", false, "a", 1),
+                    ("gone.txt", false, "b", 1),
+                    ("dir", true, "", 0)
+                ],
+                ConflictPolicy::KeepBoth,
+            ),
+            [
+                "mkdir remote",
+                "upload new.txt",
+                "delete remote gone.txt",
+                "delete local dir",
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_and_conflicts() {
+        let records = [("a.txt", false, "1", 1), ("dir/b.txt", false, "1", 1)];
+        let local = [
+            ("a.txt", false, 2),
+            ("dir", true, 0),
+            ("dir/b.txt", false, 1),
+        ];
+        let remote = [
+            ("a.txt", false, "2"),
+            ("dir", true, ""),
+            ("dir/b.txt", false, "2"),
+        ];
+        assert_eq!(
+            plan_for(&local, &remote, &records, ConflictPolicy::KeepBoth),
+            [
+                "conflict a.txt (local copy as a (conflict).txt)",
+                "download dir/b.txt"
+            ]
+        );
+        assert_eq!(
+            plan_for(&local, &remote, &records, ConflictPolicy::LocalWins),
+            ["upload a.txt", "download dir/b.txt"]
+        );
+    }
+
+    #[test]
+    fn deleted_directory_with_changes() {
+        // Remote deleted `dir`, but a file was added locally.
+        assert_eq!(
+            plan_for(
+                &[
+                    ("dir", true, 0),
+                    ("dir/old", false, 1),
+                    ("dir/new", false, 1)
+                ],
+                &[],
+                &[("dir", true, "", 0), ("dir/old", false, "1", 1)],
+                ConflictPolicy::KeepBoth,
+            ),
+            ["mkcol dir", "upload dir/new", "delete local dir/old"]
+        );
+        // Local deleted `dir` without changes remotely.
+        assert_eq!(
+            plan_for(
+                &[],
+                &[("dir", true, ""), ("dir/old", false, "1")],
+                &[("dir", true, "", 0), ("dir/old", false, "1", 1)],
+                ConflictPolicy::KeepBoth,
+            ),
+            ["delete remote dir/old", "delete remote dir"]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn deleted_remotely_keeps_excluded() {
+        let root = std::env::temp_dir().join(format!(
+            "webdav-sync-deleted-remotely-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(root.join("dir")).unwrap();
+        fs::write(root.join("dir/file"), "a").unwrap();
+        fs::write(root.join("dir/x.tmp"), "b").unwrap();
+        std::os::unix::fs::symlink("../outside", root.join("dir/link")).unwrap();
+        let modified = fs::metadata(root.join("dir/file")).unwrap().modified().ok();
+        let mut state = synced_dir(modified);
+
+        let client = Mock::client(|_| listing(&[""]));
+        let options = SyncOptions::new().exclude("*.tmp");
+        let plan = client
+            .sync_plan(&root, "https://example.com/dav/", &state, &options)
+            .unwrap();
+        assert_eq!(plan.actions, [
+            SyncAction::DeleteLocal("dir/file".to_owned()),
+            SyncAction::DeleteLocal("dir".to_owned()),
+        ]);
+        let result = client.sync(&plan, &mut state);
+        let mut kept: Vec<_> = fs::read_dir(root.join("dir"))
+            .map(|entries| {
+                entries
+                    .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        kept.sort();
+        fs::remove_dir_all(&root).unwrap();
+        result.unwrap();
+        assert_eq!(kept, ["link", "x.tmp"]);
+        assert!(state.records.is_empty());
+    }
+
+    #[test]
+    fn deleted_locally_keeps_excluded() {
+        let root = std::env::temp_dir().join(format!(
+            "webdav-sync-deleted-locally-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&root).unwrap();
+        let mut state = synced_dir(None);
+
+        let client = Mock::client(|sent| match (sent.method.as_str(), sent.header("depth")) {
+            ("PROPFIND", Some("infinity")) => listing(&["", "dir/", "dir/file", "dir/x.tmp"]),
+            ("PROPFIND", _) => listing(&["dir/", "dir/x.tmp"]),
+            _ => MockResponse::new(204),
+        });
+        let options = SyncOptions::new().exclude("*.tmp");
+        let plan = client
+            .sync_plan(&root, "https://example.com/dav/", &state, &options)
+            .unwrap();
+        assert_eq!(plan.actions, [
+            SyncAction::DeleteRemote("dir/file".to_owned()),
+            SyncAction::DeleteRemote("dir".to_owned()),
+        ]);
+        let result = client.sync(&plan, &mut state);
+        fs::remove_dir_all(&root).unwrap();
+        result.unwrap();
+
+        let sent = client.web_client.sent();
+        assert_eq!(
+            sent.iter()
+                .map(|sent| (sent.method.as_str(), sent.url.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("PROPFIND", "https://example.com/dav/"),
+                ("DELETE", "https://example.com/dav/dir/file"),
+                ("PROPFIND", "https://example.com/dav/dir/"),
+            ]
+        );
+        assert_eq!(sent[1].header("if-match"), Some(r#""1""#));
+        assert!(state.records.is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn symlink_loop() {
+        let root = std::env::temp_dir().join(format!("webdav-sync-symlink-{}", std::process::id()));
+        fs::create_dir_all(root.join("dir")).unwrap();
+        fs::write(root.join("dir/file"), "a").unwrap();
+        std::os::unix::fs::symlink("..", root.join("dir/loop")).unwrap();
+        std::os::unix::fs::symlink("file", root.join("dir/link")).unwrap();
+        let entries = scan_local(&root, |_| false);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(entries.unwrap().into_keys().collect::<Vec<_>>(), [
+            "dir", "dir/file"
+        ]);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let path = std::env::temp_dir().join(format!("webdav-sync-state-{}", std::process::id()));
+        let state = SyncState {
+            remote: Some("https://example.com/dav/".to_owned()),
+            records: [
+                ("dir".to_owned(), SyncRecord {
+                    collection: true,
+                    ..SyncRecord::default()
+                }),
+                ("dir/tab\tname%.txt".to_owned(), SyncRecord {
+                    collection: false,
+                    etag: Some("\"abc\"".to_owned()),
+                    remote_modified: Some(
+                        OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap(),
+                    ),
+                    size: 42,
+                    local_modified: Some(
+                        UNIX_EPOCH + Duration::from_nanos(1_700_000_000_123_456_789),
+                    ),
+                    hash: Some("00ff".to_owned()),
+                }),
+            ]
+            .into(),
+        };
+        state.save(&path).unwrap();
+        let loaded = SyncState::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.remote, state.remote);
+        assert_eq!(loaded.records, state.records);
+    }
+}
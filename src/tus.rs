@@ -0,0 +1,436 @@
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+
+use super::*;
+
+/// Version of the tus protocol sent in `Tus-Resumable`.
+const TUS_VERSION: &[u8] = b"1.0.0";
+const OFFSET_OCTET_STREAM: &[u8] = b"application/offset+octet-stream";
+const DEFAULT_CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Support for the [tus](https://tus.io/protocols/resumable-upload)
+/// resumable upload protocol, see [`ServerCapabilities::tus`].
+pub struct TusCapabilities {
+    /// Protocol versions from the `Tus-Version` header.
+    pub versions: Vec<String>,
+    /// Extensions from the `Tus-Extension` header, e.g., `creation` and
+    /// `creation-with-upload`.
+    pub extensions: Vec<String>,
+    /// Maximum size of an upload in bytes from the `Tus-Max-Size` header.
+    pub max_size: Option<u64>,
+}
+
+impl TusCapabilities {
+    /// Whether the server supports the extension `extension`
+    /// (case-insensitive).
+    #[must_use]
+    pub fn supports(&self, extension: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|supported| supported.eq_ignore_ascii_case(extension))
+    }
+
+    /// Parses the tus headers of an `OPTIONS` response, [`None`] if the
+    /// server does not support tus.
+    pub(crate) fn from_response(response: &impl Response) -> Option<Self> {
+        let list = |name| {
+            options::header_list(response, name)
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        };
+        let versions = list("tus-version");
+        if versions.is_empty() && response.header("tus-resumable").is_none() {
+            return None;
+        }
+        Some(Self {
+            versions,
+            extensions: list("tus-extension"),
+            max_size: response
+                .header_str("tus-max-size")
+                .and_then(|value| value.trim().parse().ok()),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+/// Options for [`Client::tus_upload`].
+///
+/// ```
+/// # use webdav_client::TusUpload;
+/// let options = TusUpload::new()
+///     .metadata("filename", "disk.img")
+///     .chunk_size(32 * 1024 * 1024);
+/// ```
+pub struct TusUpload {
+    chunk_size: u64,
+    retries: usize,
+    upload_url: Option<String>,
+    metadata: Vec<(String, String)>,
+}
+
+impl Default for TusUpload {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            retries: 3,
+            upload_url: None,
+            metadata: Vec::new(),
+        }
+    }
+}
+
+impl TusUpload {
+    /// Options creating a new upload, sent in chunks of `10 * 1024 * 1024`
+    /// bytes with up to 3 retries.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the `PATCH` requests.
+    #[must_use]
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets how often a failed request is retried from the offset reported
+    /// by the server, before giving up.
+    #[must_use]
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Continues the upload at `upload_url`, e.g., created by
+    /// [`Client::tus_create`], instead of creating a new one.
+    #[must_use]
+    pub fn resume(mut self, upload_url: impl Into<String>) -> Self {
+        self.upload_url = Some(upload_url.into());
+        self
+    }
+
+    /// Adds `key` with `value` to the `Upload-Metadata` of a new upload,
+    /// e.g., `filename`, which ownCloud Infinite Scale uses as name of the
+    /// resource.
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    fn upload_metadata(&self) -> Vec<u8> {
+        self.metadata
+            .iter()
+            .map(|(key, value)| {
+                if value.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{key} {}", BASE64_STANDARD.encode(value))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+            .into_bytes()
+    }
+}
+
+impl<A: Asyncness, T: WebClient<Asyncness = A>> Client<T> {
+    /// Creates a tus upload of `length` bytes at the endpoint `url`, returns
+    /// the absolute URL of the upload.
+    ///
+    /// Only the [`metadata`](TusUpload::metadata) of `options` is used.
+    pub fn tus_create(
+        &self,
+        url: impl AsRef<str>,
+        length: u64,
+        options: &TusUpload,
+    ) -> A::Future<Result<String>> {
+        let url = self.url(url);
+        let response = self.tus_creation(&url, length, options).send_ok(None);
+        A::and_then(response, move |response| upload_location(&url, &response))
+    }
+
+    /// Queries the offset of the tus upload at `upload_url` using `HEAD`,
+    /// i.e., the number of bytes the server received.
+    pub fn tus_offset(&self, upload_url: impl AsRef<str>) -> A::Future<Result<u64>> {
+        let response = self
+            .request("HEAD", upload_url.as_ref())
+            .header(b"tus-resumable", TUS_VERSION.to_vec())
+            .header(b"cache-control", b"no-store".to_vec())
+            .send_ok(None);
+        A::and_then(response, |response| upload_offset(&response))
+    }
+
+    /// Appends `chunk` at `offset` to the tus upload at `upload_url` using
+    /// `PATCH`, returns the new offset.
+    ///
+    /// Fails with `409 Conflict` if `offset` does not match the offset of the
+    /// upload.
+    pub fn tus_patch(
+        &self,
+        upload_url: impl AsRef<str>,
+        offset: u64,
+        chunk: Vec<u8>,
+    ) -> A::Future<Result<u64>> {
+        let response = self
+            .request("PATCH", upload_url.as_ref())
+            .header(b"tus-resumable", TUS_VERSION.to_vec())
+            .header(b"upload-offset", offset.to_string().into_bytes())
+            .header(b"content-type", OFFSET_OCTET_STREAM.to_vec())
+            .send_ok(Some(chunk));
+        A::and_then(response, |response| upload_offset(&response))
+    }
+
+    /// `POST` request creating an upload of `length` bytes.
+    fn tus_creation(
+        &self,
+        url: &str,
+        length: u64,
+        options: &TusUpload,
+    ) -> <Self as WebClient>::Request {
+        let request = self
+            .request("POST", url)
+            .header(b"tus-resumable", TUS_VERSION.to_vec())
+            .header(b"upload-length", length.to_string().into_bytes());
+        if options.metadata.is_empty() {
+            request
+        } else {
+            request.header(b"upload-metadata", options.upload_metadata())
+        }
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Client<T> {
+    /// Uploads `source` to the tus endpoint at `url`, e.g., a collection of
+    /// ownCloud Infinite Scale, returns the URL of the upload.
+    ///
+    /// A new upload is created with the first chunk (`creation-with-upload`),
+    /// the remaining chunks are sent with `PATCH`. When a request fails, the
+    /// upload continues from the offset reported by the server.
+    ///
+    /// To continue an upload after the process was interrupted, create it
+    /// with [`tus_create`](Self::tus_create), store its URL and pass it to
+    /// [`TusUpload::resume`].
+    ///
+    /// ```no_run
+    /// # use webdav_client::{Blocking, Client, TusUpload, WebClient};
+    /// # fn upload(client: &Client<impl WebClient<Asyncness = Blocking>>) -> webdav_client::Result<()> {
+    /// let file = std::fs::File::open("disk.img")?;
+    /// let options = TusUpload::new().metadata("filename", "disk.img");
+    /// client.tus_upload("images/", file, &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tus_upload(
+        &self,
+        url: impl AsRef<str>,
+        mut source: impl Read + Seek,
+        options: &TusUpload,
+    ) -> Result<String> {
+        let url = self.url(url);
+        let length = source.seek(SeekFrom::End(0))?;
+        let (upload, mut offset) = if let Some(upload_url) = &options.upload_url {
+            let upload_url = self.url(upload_url);
+            let offset = self.tus_offset(&upload_url)?;
+            (upload_url, offset)
+        } else {
+            let chunk = read_chunk(&mut source, 0, options.chunk_size.min(length))?;
+            let response = self
+                .tus_creation(&url, length, options)
+                .header(b"content-type", OFFSET_OCTET_STREAM.to_vec())
+                .send_ok(Some(chunk))?;
+            let upload = upload_location(&url, &response)?;
+            // Servers not supporting `creation-with-upload` ignore the body.
+            let offset = match response.header("upload-offset") {
+                Some(_) => upload_offset(&response)?,
+                None => 0,
+            };
+            (upload, offset)
+        };
+
+        let mut failures = 0;
+        while check_offset(offset, length)? < length {
+            let chunk = read_chunk(&mut source, offset, options.chunk_size.min(length - offset))?;
+            match self.tus_patch(&upload, offset, chunk) {
+                Ok(new_offset) if new_offset <= offset => {
+                    return Err(Error::InvalidResponse(format!(
+                        "Upload-Offset {new_offset} did not advance from {offset}"
+                    )));
+                }
+                Ok(new_offset) => {
+                    offset = new_offset;
+                    failures = 0;
+                }
+                Err(error) if failures < options.retries && is_retryable(&error) => {
+                    failures += 1;
+                    offset = self.tus_offset(&upload)?;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(upload)
+    }
+}
+
+/// Reads `size` bytes at `offset` from `source`.
+fn read_chunk(source: &mut (impl Read + Seek), offset: u64, size: u64) -> Result<Vec<u8>> {
+    source.seek(SeekFrom::Start(offset))?;
+    let mut chunk = Vec::new();
+    source.take(size).read_to_end(&mut chunk)?;
+    if chunk.len() as u64 == size {
+        Ok(chunk)
+    } else {
+        Err(io::Error::from(ErrorKind::UnexpectedEof).into())
+    }
+}
+
+/// Fails if the server reports an `offset` beyond the `length` of the upload.
+fn check_offset(offset: u64, length: u64) -> Result<u64> {
+    if offset > length {
+        Err(Error::InvalidResponse(format!(
+            "Upload-Offset {offset} exceeds Upload-Length {length}"
+        )))
+    } else {
+        Ok(offset)
+    }
+}
+
+/// Whether a failed `PATCH` could succeed when retried from the offset
+/// reported by the server, i.e., it failed in transit, because of a wrong
+/// offset or a server error.
+fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::WebRequest(_) => true,
+        Error::ErrorStatus { status, .. } => *status == 409 || *status >= 500,
+        _ => false,
+    }
+}
+
+fn upload_location(url: &str, response: &impl Response) -> Result<String> {
+    let location = response
+        .header_str("location")
+        .ok_or_else(|| Error::InvalidResponse("tus upload created without Location".into()))?;
+    Ok(url::resolve(url, location))
+}
+
+fn upload_offset(response: &impl Response) -> Result<u64> {
+    response
+        .header_str("upload-offset")
+        .and_then(|offset| offset.trim().parse().ok())
+        .ok_or_else(|| Error::InvalidResponse("missing or invalid Upload-Offset".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::mock::{Mock, MockResponse, Sent};
+
+    #[test]
+    fn metadata() {
+        let options = TusUpload::new()
+            .metadata("filename", "disk.img")
+            .metadata("empty", "");
+        assert_eq!(options.upload_metadata(), b"filename ZGlzay5pbWc=,empty");
+    }
+
+    #[test]
+    fn headers() {
+        let response = MockResponse::new(201)
+            .header("Location", "uploads/1")
+            .header("Upload-Offset", " 42 ");
+        assert_eq!(
+            upload_location("https://example.com/files/", &response).unwrap(),
+            "https://example.com/files/uploads/1"
+        );
+        assert_eq!(upload_offset(&response).unwrap(), 42);
+
+        let response = MockResponse::new(201).header("Upload-Offset", "many");
+        assert!(matches!(
+            upload_location("https://example.com/files/", &response),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            upload_offset(&response),
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn retryable() {
+        let status = |status| Error::ErrorStatus { status, text: None };
+        assert!(is_retryable(&Error::web_request(io::Error::from(
+            ErrorKind::ConnectionReset
+        ))));
+        assert!(is_retryable(&status(409)));
+        assert!(is_retryable(&status(503)));
+        assert!(!is_retryable(&status(404)));
+        assert!(!is_retryable(&Error::PreconditionFailed { text: None }));
+    }
+
+    /// Uploads 10 bytes in chunks of 4, with the server answering `PATCH`
+    /// with `patch`.
+    fn upload(patch: impl Fn(u64) -> MockResponse + Send + 'static) -> (Result<String>, Vec<Sent>) {
+        let client = Mock::client(move |sent| match sent.method.as_str() {
+            "POST" => MockResponse::new(201)
+                .header("Location", "/uploads/1")
+                .header("Upload-Offset", "4"),
+            "PATCH" => patch(sent.header("upload-offset").unwrap().parse().unwrap()),
+            "HEAD" => MockResponse::new(200).header("Upload-Offset", "4"),
+            _ => MockResponse::new(405),
+        });
+        let result = client.tus_upload(
+            "https://example.com/files/",
+            Cursor::new(vec![0; 10]),
+            &TusUpload::new().chunk_size(4),
+        );
+        (result, client.web_client.sent())
+    }
+
+    #[test]
+    fn upload_offsets() {
+        let advance = |offset: u64| {
+            MockResponse::new(204).header("Upload-Offset", (offset + 4).min(10).to_string())
+        };
+        let (result, sent) = upload(advance);
+        assert_eq!(result.unwrap(), "https://example.com/uploads/1");
+        let sent: Vec<_> = sent
+            .iter()
+            .map(|sent| {
+                (
+                    sent.method.as_str(),
+                    sent.url.as_str(),
+                    sent.header("upload-offset"),
+                    sent.body.len(),
+                )
+            })
+            .collect();
+        assert_eq!(sent, [
+            ("POST", "https://example.com/files/", None, 4),
+            ("PATCH", "https://example.com/uploads/1", Some("4"), 4),
+            ("PATCH", "https://example.com/uploads/1", Some("8"), 2),
+        ]);
+
+        let stuck =
+            |offset: u64| MockResponse::new(204).header("Upload-Offset", offset.to_string());
+        assert!(matches!(upload(stuck).0, Err(Error::InvalidResponse(_))));
+
+        let beyond = |_| MockResponse::new(204).header("Upload-Offset", "11");
+        assert!(matches!(upload(beyond).0, Err(Error::InvalidResponse(_))));
+
+        // The server keeps rejecting the offset it reports itself.
+        let conflict = |_| MockResponse::new(409);
+        let (result, sent) = upload(conflict);
+        assert!(matches!(
+            result,
+            Err(Error::ErrorStatus { status: 409, .. })
+        ));
+        // One `PATCH` plus three retries, each after a `HEAD`.
+        assert_eq!(sent.iter().filter(|sent| sent.method == "PATCH").count(), 4);
+    }
+}
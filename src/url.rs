@@ -0,0 +1,306 @@
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+
+use super::*;
+
+/// Characters that are percent-encoded in a path segment, i.e., everything
+/// but `pchar` from [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.3).
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b'+')
+    .remove(b',')
+    .remove(b';')
+    .remove(b'=')
+    .remove(b':')
+    .remove(b'@');
+
+impl<T> Client<T> {
+    /// Sets the base URL paths passed to the [`Client`] are resolved
+    /// against, see [`url`](Self::url).
+    ///
+    /// ```
+    /// # use webdav_client::Client;
+    /// let client = Client::new(()).with_base_url("https://example.com/dav/files/user");
+    /// assert_eq!(
+    ///     client.url("Documents/Read me.md"),
+    ///     "https://example.com/dav/files/user/Documents/Read%20me.md"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Resolves `path` against the [base URL](Self::with_base_url).
+    ///
+    /// `path` is percent-encoded and joined according to
+    /// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-5.2), with
+    /// the base URL always treated as a collection, i.e., `a/b` is appended to
+    /// it, while `/a/b` replaces its path and `..` refers to its parent.
+    ///
+    /// Valid percent-escapes like `%20` are kept, so already encoded paths,
+    /// e.g., hrefs, are not encoded twice. A literal `%` followed by two hex
+    /// digits needs to be written as `%25`.
+    ///
+    /// Absolute URLs (starting with a scheme and `://`) and all URLs of
+    /// clients without a base URL are returned unchanged.
+    pub fn url(&self, path: impl AsRef<str>) -> String {
+        let path = path.as_ref();
+        match &self.base_url {
+            Some(base_url) if scheme(path).is_none() => {
+                let (origin, base_path) = split(base_url);
+                let base_path = if base_path.ends_with('/') {
+                    format!("{origin}{base_path}")
+                } else {
+                    format!("{origin}{base_path}/")
+                };
+                resolve(&base_path, &encode_path(path))
+            }
+            _ => path.to_owned(),
+        }
+    }
+}
+
+/// Percent-encodes every segment of `path`, keeping the `/` between them and
+/// valid percent-escapes.
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = path;
+    while let Some(index) = rest.find('%') {
+        encoded.push_str(&escape_path(&rest[..index]));
+        if let Some(escape) = rest[index..]
+            .get(..3)
+            .filter(|escape| escape[1..].bytes().all(|byte| byte.is_ascii_hexdigit()))
+        {
+            encoded.push_str(escape);
+            rest = &rest[index + 3..];
+        } else {
+            encoded.push_str("%25");
+            rest = &rest[index + 1..];
+        }
+    }
+    encoded.push_str(&escape_path(rest));
+    encoded
+}
+
+/// Percent-encodes every segment of the decoded `path`, keeping the `/`
+/// between them, e.g., a path returned by [`relative_path`].
+pub(crate) fn escape_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| utf8_percent_encode(segment, SEGMENT).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Scheme of `url` if it starts with `scheme://`, i.e., is absolute, see
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-3.1).
+fn scheme(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once(':')?;
+    let mut chars = scheme.chars();
+    (chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && rest.starts_with("//"))
+    .then_some(scheme)
+}
+
+/// Splits an absolute `url` into `scheme://authority` and its path, dropping
+/// query and fragment.
+fn split(url: &str) -> (&str, &str) {
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let authority = scheme(url).map_or(0, |scheme| scheme.len() + 3);
+    let path = url[authority..]
+        .find('/')
+        .map_or(url.len(), |index| authority + index);
+    url.split_at(path)
+}
+
+/// Resolves `reference` against the absolute URL `base`.
+pub(crate) fn resolve(base: &str, reference: &str) -> String {
+    if scheme(reference).is_some() {
+        return reference.to_owned();
+    }
+    let (origin, base_path) = split(base);
+    if let Some(network_path) = reference.strip_prefix("//") {
+        return format!("{}://{network_path}", scheme(origin).unwrap_or_default());
+    }
+    let reference = reference.split(['?', '#']).next().unwrap_or_default();
+    let path = if reference.is_empty() {
+        base_path.to_owned()
+    } else if reference.starts_with('/') {
+        remove_dot_segments(reference)
+    } else {
+        let directory = base_path
+            .rfind('/')
+            .map_or("/", |index| &base_path[..=index]);
+        remove_dot_segments(&format!("{directory}{reference}"))
+    };
+    format!("{origin}{path}")
+}
+
+/// Removes `.` and `..` segments from an absolute path, see
+/// [RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-5.2.4).
+fn remove_dot_segments(path: &str) -> String {
+    let mut output = Vec::new();
+    let mut segments = path.strip_prefix('/').unwrap_or(path).split('/').peekable();
+    while let Some(segment) = segments.next() {
+        match segment {
+            "." | ".." => {
+                if segment == ".." {
+                    output.pop();
+                }
+                if segments.peek().is_none() {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+    format!("/{}", output.join("/"))
+}
+
+/// Resolves `href` against `request_url` and returns the decoded path
+/// relative to it, see [`webdav_types::Response::relative_path`].
+pub(crate) fn relative_path(request_url: &str, href: &str) -> Option<String> {
+    let decode = |path: &str| percent_decode_str(path).decode_utf8_lossy().into_owned();
+    let path = decode(split(&resolve(request_url, href)).1);
+    let base = decode(&remove_dot_segments(split(request_url).1));
+    let relative = strip_segments(&path, base.trim_end_matches('/'))?;
+    Some(relative.trim_matches('/').to_owned())
+}
+
+/// Strips `prefix` from `path` at a segment boundary, i.e., `/dav` is a
+/// prefix of `/dav/a`, but not of `/davx/a`.
+fn strip_segments<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    path.strip_prefix(prefix)
+        .filter(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_rfc() {
+        // https://www.rfc-editor.org/rfc/rfc3986#section-5.4
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            (";x", "http://a/b/c/;x"),
+            ("", "http://a/b/c/d;p"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("https://other/g", "https://other/g"),
+        ] {
+            assert_eq!(resolve(base, reference), expected, "{reference}");
+        }
+    }
+
+    #[test]
+    fn base_url() {
+        let client = Client::new(()).with_base_url("https://example.com/dav/");
+        assert_eq!(client.url(""), "https://example.com/dav/");
+        assert_eq!(
+            client.url("a b/c#?.txt"),
+            "https://example.com/dav/a%20b/c%23%3F.txt"
+        );
+        assert_eq!(client.url("../other"), "https://example.com/other");
+        // Existing escapes are not encoded twice.
+        assert_eq!(
+            client.url("foo%20bar/%E2%82%ACuro"),
+            "https://example.com/dav/foo%20bar/%E2%82%ACuro"
+        );
+        assert_eq!(
+            client.url("100%/%2x/%"),
+            "https://example.com/dav/100%25/%252x/%25"
+        );
+        assert_eq!(client.url("/root"), "https://example.com/root");
+        assert_eq!(client.url("http://other/x"), "http://other/x");
+        assert_eq!(
+            client.url("a/b?x=http://y"),
+            "https://example.com/dav/a/b%3Fx=http://y"
+        );
+        assert_eq!(client.url("a:b//c"), "https://example.com/dav/a:b//c");
+        assert_eq!(Client::new(()).url("a b"), "a b");
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(escape_path("a b/100%20/€"), "a%20b/100%2520/%E2%82%AC");
+        assert_eq!(encode_path("a b/100%20/€"), "a%20b/100%20/%E2%82%AC");
+    }
+
+    #[test]
+    fn relative_outside_base() {
+        let url = "https://example.com/cloud/dav/";
+        for (href, expected) in [
+            ("/cloud/dav/a", Some("a")),
+            ("/cloud/dav", Some("")),
+            ("/dav/a", None),
+            ("/davx/a", None),
+            ("/cloud/davx/a", None),
+            ("/clouddav/a", None),
+        ] {
+            assert_eq!(relative_path(url, href).as_deref(), expected, "{href}");
+        }
+    }
+
+    #[test]
+    fn schemes() {
+        assert_eq!(scheme("https://example.com"), Some("https"));
+        assert_eq!(scheme("svn+ssh://example.com"), Some("svn+ssh"));
+        for url in [
+            "a/b?x=http://y",
+            "/a://b",
+            "1a://b",
+            "mailto:a@b",
+            "://a",
+            "",
+        ] {
+            assert_eq!(scheme(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn relative() {
+        let url = "https://example.com/dav/files/user/";
+        for (href, expected) in [
+            ("/dav/files/user/", Some("")),
+            ("/dav/files/user", Some("")),
+            ("/dav/files/user/a%20b.txt", Some("a b.txt")),
+            ("https://example.com/dav/files/user/dir/", Some("dir")),
+            ("dir/file", Some("dir/file")),
+            ("/files/user/a", None),
+            ("/user/a", None),
+            ("/dav/files/username", None),
+            ("/other", None),
+        ] {
+            assert_eq!(relative_path(url, href).as_deref(), expected, "{href}");
+        }
+    }
+}
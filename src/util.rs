@@ -0,0 +1,10 @@
+//! Helpers shared between modules.
+use std::fmt::Write;
+
+/// Lowercase hex encoding of `bytes`, e.g., of a hash.
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{byte:02x}").unwrap();
+        hex
+    })
+}
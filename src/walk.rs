@@ -0,0 +1,398 @@
+use std::collections::VecDeque;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::task::{Context, Poll, ready};
+
+#[cfg(feature = "async")]
+use futures_util::future::BoxFuture;
+#[cfg(feature = "async")]
+use futures_util::stream::{FuturesUnordered, Stream, StreamExt};
+use webdav_types::DavProperties;
+
+use super::*;
+
+/// Properties requested for every resource by [`Walk`].
+const PROPERTIES: [&str; 7] = [
+    "resourcetype",
+    "getetag",
+    "getlastmodified",
+    "creationdate",
+    "getcontentlength",
+    "getcontenttype",
+    "displayname",
+];
+
+#[derive(Debug, Clone)]
+/// Resource found by [`Client::walk`].
+pub struct WalkEntry {
+    /// Decoded path relative to the walked collection, see
+    /// [`webdav_types::Response::relative_path`].
+    pub path: String,
+    /// Absolute URL of the resource, as returned by the server.
+    pub url: String,
+    /// Number of segments in [`path`](Self::path), `1` for direct members of
+    /// the walked collection.
+    pub depth: usize,
+    pub properties: DavProperties,
+}
+
+impl WalkEntry {
+    /// Whether the resource is a collection (directory).
+    #[must_use]
+    pub fn is_collection(&self) -> bool {
+        self.properties.is_collection()
+    }
+}
+
+/// Recursive traversal of a collection, see [`Client::walk`].
+///
+/// An [`Iterator`] for [`Blocking`] clients and a `Stream` for `Async`
+/// clients, yielding every member but the walked collection itself.
+///
+/// A `Depth: infinity` request is tried first and, if the server refuses it,
+/// every collection is listed with `Depth: 1` instead.
+pub struct Walk<'a, T> {
+    client: &'a Client<T>,
+    url: String,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    max_depth: Option<usize>,
+    concurrency: usize,
+    infinity: bool,
+    /// Collections still to list, as path and URL.
+    pending: Vec<(String, String)>,
+    entries: VecDeque<Result<WalkEntry>>,
+    #[cfg(feature = "async")]
+    running: FuturesUnordered<BoxFuture<'static, (String, Depth, Result<MultiStatus>)>>,
+}
+
+impl<T> Client<T> {
+    /// Recursively walks the collection at `url`.
+    ///
+    /// ```no_run
+    /// # use webdav_client::{Blocking, Client, WebClient};
+    /// # fn walk(client: &Client<impl WebClient<Asyncness = Blocking>>) -> webdav_client::Result<()> {
+    /// for entry in client.walk("Photos").exclude("*.tmp").max_depth(3) {
+    ///     let entry = entry?;
+    ///     println!("{} {:?}", entry.path, entry.properties.content_length);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn walk(&self, url: impl AsRef<str>) -> Walk<'_, T> {
+        let mut url = self.url(url);
+        if !url.ends_with('/') {
+            url.push('/');
+        }
+        Walk {
+            client: self,
+            pending: vec![(String::new(), url.clone())],
+            url,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            max_depth: None,
+            concurrency: 1,
+            infinity: true,
+            entries: VecDeque::new(),
+            #[cfg(feature = "async")]
+            running: FuturesUnordered::new(),
+        }
+    }
+}
+
+impl<T> Walk<'_, T> {
+    /// Only yields resources matching `glob`, collections are still
+    /// traversed.
+    ///
+    /// Globs support `*` and `?` within a segment and `**` for any number of
+    /// segments. Globs without `/` are matched against the name of the
+    /// resource, others against its whole [`path`](WalkEntry::path).
+    #[must_use]
+    pub fn include(mut self, glob: impl Into<String>) -> Self {
+        self.include.push(glob.into());
+        self
+    }
+
+    /// Skips resources matching `glob`, including all members of matching
+    /// collections, see [`include`](Self::include) for the syntax.
+    #[must_use]
+    pub fn exclude(mut self, glob: impl Into<String>) -> Self {
+        self.exclude.push(glob.into());
+        self
+    }
+
+    /// Limits the traversal to [`depth`](WalkEntry::depth) `max_depth`, `1`
+    /// only yields the direct members.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Lists up to `concurrency` collections at once, only used by `Async`
+    /// clients.
+    #[must_use]
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    fn is_excluded(&self, path: &str) -> bool {
+        path.match_indices('/')
+            .map(|(index, _)| &path[..index])
+            .chain([path])
+            .any(|path| self.exclude.iter().any(|glob| glob_match(glob, path)))
+    }
+
+    fn is_included(&self, path: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|glob| glob_match(glob, path))
+    }
+
+    /// Next collection to list, with the depth to list it with.
+    fn next_listing(&mut self) -> Option<(String, String, Depth)> {
+        let (path, url) = self.pending.pop()?;
+        let depth = if self.infinity && self.max_depth.is_none() {
+            Depth::Infinity
+        } else {
+            Depth::Some(1)
+        };
+        self.infinity = false;
+        Some((path, url, depth))
+    }
+
+    fn process(&mut self, collection: String, depth: Depth, result: Result<MultiStatus>) {
+        let multi_status = match result {
+            Ok(multi_status) => multi_status,
+            // RFC 4918 only specifies `403` with `DAV:propfind-finite-depth`,
+            // but some servers use other statuses.
+            Err(Error::ErrorStatus {
+                status: 400 | 403 | 501,
+                ..
+            }) if depth == Depth::Infinity => {
+                let url = self.url.clone();
+                self.pending.push((collection, url));
+                return;
+            }
+            Err(error) => {
+                self.entries.push_back(Err(error));
+                return;
+            }
+        };
+        let mut collections = Vec::new();
+        for response in multi_status {
+            if !response.is_successful() {
+                continue;
+            }
+            let Some(path) = response.relative_path(&self.url) else {
+                continue;
+            };
+            if path == collection || path.is_empty() {
+                continue;
+            }
+            let entry_depth = path.split('/').count();
+            if self.max_depth.is_some_and(|max| entry_depth > max) || self.is_excluded(&path) {
+                continue;
+            }
+            let properties = match response.dav_properties() {
+                Ok(properties) => properties,
+                Err(error) => {
+                    self.entries.push_back(Err(error.into()));
+                    continue;
+                }
+            };
+            let url = url::resolve(&self.url, &response.href);
+            if depth != Depth::Infinity
+                && properties.is_collection()
+                && self.max_depth.is_none_or(|max| entry_depth < max)
+            {
+                collections.push((path.clone(), url.clone()));
+            }
+            if self.is_included(&path) {
+                self.entries.push_back(Ok(WalkEntry {
+                    path,
+                    url,
+                    depth: entry_depth,
+                    properties,
+                }));
+            }
+        }
+        self.pending.extend(collections.into_iter().rev());
+    }
+}
+
+impl<T: WebClient<Asyncness = A>, A: Asyncness> Walk<'_, T> {
+    fn list(&self, url: &str, depth: Depth) -> A::Future<Result<MultiStatus>> {
+        let properties = PROPERTIES.map(QualifiedName::dav).into();
+        self.client
+            .prop_find_with(url, depth, &PropFind::Prop(properties))
+    }
+}
+
+impl<T: WebClient<Asyncness = Blocking>> Iterator for Walk<'_, T> {
+    type Item = Result<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.pop_front() {
+                return Some(entry);
+            }
+            let (path, url, depth) = self.next_listing()?;
+            let result = self.list(&url, depth);
+            self.process(path, depth, result);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: WebClient<Asyncness = Async>> Stream for Walk<'_, T> {
+    type Item = Result<WalkEntry>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(entry) = this.entries.pop_front() {
+                return Poll::Ready(Some(entry));
+            }
+            while this.running.len() < this.concurrency {
+                let Some((path, url, depth)) = this.next_listing() else {
+                    break;
+                };
+                let listing = this.list(&url, depth);
+                this.running
+                    .push(Async::map(listing, move |result| (path, depth, result)));
+            }
+            match ready!(this.running.poll_next_unpin(cx)) {
+                Some((path, depth, result)) => this.process(path, depth, result),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+/// Matches `path` against `glob`, see [`Walk::include`].
+pub(crate) fn glob_match(glob: &str, path: &str) -> bool {
+    fn matches(glob: &[char], path: &[char]) -> bool {
+        match glob {
+            [] => path.is_empty(),
+            ['*', '*', rest @ ..] => {
+                (0..=path.len()).any(|index| matches(rest, &path[index..]))
+                    || rest.first() == Some(&'/') && matches(&rest[1..], path)
+            }
+            ['*', rest @ ..] => (0..=path.len())
+                .take_while(|&index| index == 0 || path[index - 1] != '/')
+                .any(|index| matches(rest, &path[index..])),
+            ['?', rest @ ..] => {
+                path.first().is_some_and(|&c| c != '/') && matches(rest, &path[1..])
+            }
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+
+    let path = if glob.contains('/') {
+        path
+    } else {
+        path.rsplit('/').next().unwrap_or(path)
+    };
+    let glob: Vec<_> = glob.chars().collect();
+    let path: Vec<_> = path.chars().collect();
+    matches(&glob, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob() {
+        for (glob, path, expected) in [
+            ("*.txt", "a.txt", true),
+            ("*.txt", "dir/a.txt", true),
+            ("*.txt", "a.txt/b", false),
+            ("dir/*.txt", "dir/a.txt", true),
+            ("dir/*.txt", "dir/sub/a.txt", false),
+            ("dir/**/*.txt", "dir/sub/a.txt", true),
+            ("dir/**/*.txt", "dir/a.txt", true),
+            ("**/sub", "dir/sub", true),
+            ("dir/**", "dir/sub/a.txt", true),
+            ("?.txt", "a.txt", true),
+            ("?.txt", "ab.txt", false),
+            (".git", "dir/.git", true),
+        ] {
+            assert_eq!(glob_match(glob, path), expected, "{glob} {path}");
+        }
+    }
+
+    #[test]
+    fn process() {
+        let multi_status = r#"<?xml version="1.0"?>
+            <d:multistatus xmlns:d="DAV:">
+              <d:response>
+                <d:href>/dav/</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/dav/a%20b/</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/dav/c.tmp</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype/></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+              <d:response>
+                <d:href>/dav/d.txt</d:href>
+                <d:propstat>
+                  <d:prop><d:resourcetype/><d:getcontentlength>3</d:getcontentlength></d:prop>
+                  <d:status>HTTP/1.1 200 OK</d:status>
+                </d:propstat>
+              </d:response>
+            </d:multistatus>"#;
+        let client = Client::new(());
+        let mut walk = client.walk("https://example.com/dav").exclude("*.tmp");
+        let (path, _, depth) = walk.next_listing().unwrap();
+        assert_eq!(depth, Depth::Infinity);
+
+        walk.process(
+            path.clone(),
+            depth,
+            Err(Error::ErrorStatus {
+                status: 403,
+                text: None,
+            }),
+        );
+        assert!(walk.entries.is_empty());
+        let (path, url, depth) = walk.next_listing().unwrap();
+        assert_eq!(
+            (path.as_str(), url.as_str()),
+            ("", "https://example.com/dav/")
+        );
+        assert_eq!(depth, Depth::Some(1));
+
+        walk.process(path, depth, Ok(multi_status.parse().unwrap()));
+        let entries: Vec<_> = walk
+            .entries
+            .drain(..)
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path, entry.depth, entry.properties.content_length)
+            })
+            .collect();
+        assert_eq!(entries, [
+            ("a b".to_owned(), 1, None),
+            ("d.txt".to_owned(), 1, Some(3))
+        ]);
+        assert_eq!(walk.pending, [(
+            "a b".to_owned(),
+            "https://example.com/dav/a%20b/".to_owned()
+        )]);
+    }
+}
@@ -1,7 +1,11 @@
+use std::io::{Read, Write};
+
 #[cfg(all(feature = "batteries", feature = "attohttpc"))]
 pub use attohttpc;
 #[cfg(feature = "async")]
-use futures_util::{FutureExt, TryFutureExt};
+use futures_util::{
+    AsyncWrite, AsyncWriteExt, FutureExt, Stream, StreamExt, TryFutureExt, TryStreamExt,
+};
 #[cfg(all(feature = "batteries", feature = "minreq"))]
 pub use minreq;
 #[cfg(all(feature = "batteries", feature = "reqwest"))]
@@ -13,6 +17,9 @@ use super::*;
 
 pub trait Asyncness {
     type Future<T: 'static>;
+    /// Streamed body, a [`Read`] for [`Blocking`] and a `Stream` of chunks
+    /// for `Async`.
+    type Stream: Send + 'static;
     fn ready<T: Send>(value: T) -> Self::Future<T>;
     fn map<T, O>(
         value: Self::Future<T>,
@@ -35,7 +42,7 @@ pub trait Asyncness {
 /// Web client agnostic implementation of a WebDAV client.
 pub trait WebClient {
     type Asyncness: Asyncness;
-    type Request: Request<Asyncness = Self::Asyncness, Response = Self::Response> + 'static;
+    type Request: Request<Asyncness = Self::Asyncness, Response = Self::Response> + Send + 'static;
     type Response: Response<Asyncness = Self::Asyncness> + 'static + Send;
     fn request(&self, method: &str, url: &str) -> Self::Request;
 }
@@ -59,45 +66,297 @@ pub trait Request: Sized {
         #![allow(deprecated)]
         Self::Asyncness::flat_and_then(self.send(body), Response::error_on_status_code)
     }
+    /// Like [`send`](Self::send), but streams the body, `length` is sent as
+    /// `Content-Length` if known.
+    #[must_use]
+    #[deprecated = "probably use `send_body_ok` unless you handle HTTP status codes"]
+    fn send_stream(
+        self,
+        stream: <Self::Asyncness as Asyncness>::Stream,
+        length: Option<u64>,
+    ) -> <Self::Asyncness as Asyncness>::Future<Result<Self::Response>>;
+    #[must_use]
+    fn send_body_ok(
+        self,
+        body: Body<Self::Asyncness>,
+    ) -> <Self::Asyncness as Asyncness>::Future<Result<Self::Response>> {
+        #![allow(deprecated)]
+        match body {
+            Body::Bytes(bytes) => self.send_ok(Some(bytes)),
+            Body::Stream { stream, length } => Self::Asyncness::flat_and_then(
+                self.send_stream(stream, length),
+                Response::error_on_status_code,
+            ),
+        }
+    }
+}
+
+/// Body of a request, see [`Client::put`].
+///
+/// Streams are sent without buffering them in memory, except for web clients
+/// not supporting it, i.e., [`Minreq`](crate::Minreq).
+pub enum Body<A: Asyncness> {
+    Bytes(Vec<u8>),
+    Stream {
+        stream: A::Stream,
+        /// Total length in bytes, if unknown, chunked transfer encoding is
+        /// used.
+        length: Option<u64>,
+    },
+}
+
+impl<A: Asyncness> From<Vec<u8>> for Body<A> {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Bytes(value)
+    }
+}
+
+impl<A: Asyncness> From<String> for Body<A> {
+    fn from(value: String) -> Self {
+        Self::Bytes(value.into_bytes())
+    }
+}
+
+impl Body<Blocking> {
+    /// Streams the body from `reader`.
+    pub fn reader(reader: impl Read + Send + 'static, length: Option<u64>) -> Self {
+        Self::Stream {
+            stream: Box::new(reader),
+            length,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl Body<Async> {
+    /// Streams the body from the chunks of `stream`.
+    pub fn stream(
+        stream: impl Stream<Item = Result<Vec<u8>>> + Send + 'static,
+        length: Option<u64>,
+    ) -> Self {
+        Self::Stream {
+            stream: stream.boxed(),
+            length,
+        }
+    }
+}
+
+/// Destination of [`Client::download_to`], implemented for [`Write`] with
+/// [`Blocking`] and `AsyncWrite` with `Async` web clients.
+pub trait WriteBody<A: Asyncness> {
+    /// Copies the body of `response` into `self`, returning the number of
+    /// bytes written.
+    fn write_body<R: Response<Asyncness = A> + 'static>(
+        self,
+        response: A::Future<Result<R>>,
+    ) -> A::Future<Result<u64>>;
+}
+
+impl<W: Write> WriteBody<Blocking> for W {
+    fn write_body<R: Response<Asyncness = Blocking>>(mut self, response: Result<R>) -> Result<u64> {
+        let written =
+            std::io::copy(&mut response?.stream(), &mut self).map_err(Error::web_request)?;
+        self.flush().map_err(Error::web_request)?;
+        Ok(written)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W: AsyncWrite + Unpin + Send + 'static> WriteBody<Async> for W {
+    fn write_body<R: Response<Asyncness = Async> + 'static>(
+        mut self,
+        response: BoxFuture<Result<R>>,
+    ) -> BoxFuture<Result<u64>> {
+        async move {
+            let mut stream = response.await?.stream();
+            let mut written = 0;
+            while let Some(chunk) = stream.try_next().await? {
+                self.write_all(&chunk).await.map_err(Error::web_request)?;
+                written += chunk.len() as u64;
+            }
+            self.flush().await.map_err(Error::web_request)?;
+            Ok(written)
+        }
+        .boxed()
+    }
 }
 
 pub trait Response: Sized + Send {
     type Asyncness: Asyncness;
 
     fn bytes(self) -> <Self::Asyncness as Asyncness>::Future<Result<Vec<u8>>>;
+    /// Streams the body instead of collecting it like [`bytes`](Self::bytes).
+    fn stream(self) -> <Self::Asyncness as Asyncness>::Stream;
     fn text(self) -> <Self::Asyncness as Asyncness>::Future<Result<String>> {
         <Self::Asyncness>::map(self.bytes(), |b| {
             String::from_utf8(b?).map_err(Error::web_request)
         })
     }
     fn status(&self) -> u16;
+
+    /// Value of the response header `name` (case-insensitive), the first one
+    /// if it was sent multiple times.
+    fn header(&self, name: &str) -> Option<&[u8]>;
+
+    /// All values of the response header `name` (case-insensitive), in the
+    /// order they were sent.
+    ///
+    /// `Minreq` only keeps one value per header, so it returns at most one.
+    fn header_all(&self, name: &str) -> Vec<&[u8]>;
+
+    /// Like [`header`](Self::header), but `None` if the value is not valid
+    /// UTF-8.
+    fn header_str(&self, name: &str) -> Option<&str> {
+        self.header(name)
+            .and_then(|value| str::from_utf8(value).ok())
+    }
+
     fn error_on_status_code(self) -> <Self::Asyncness as Asyncness>::Future<Result<Self>> {
         let status = self.status();
         if (200..300).contains(&status) {
             <Self::Asyncness>::ready(Ok(self))
         } else {
             Self::Asyncness::map(self.text(), move |text| {
-                Err(Error::ErrorStatus {
-                    status,
-                    text: text.ok(),
+                let text = text.ok();
+                Err(if status == 412 {
+                    Error::PreconditionFailed { text }
+                } else {
+                    Error::ErrorStatus { status, text }
                 })
             })
         }
     }
+
+    /// Turns a `207 Multi-Status` response into [`Error::MultiStatus`].
+    ///
+    /// For methods like `DELETE` or `COPY` a multi-status response means the
+    /// operation failed for some of the affected resources.
+    fn error_on_multi_status(self) -> <Self::Asyncness as Asyncness>::Future<Result<Self>> {
+        if self.status() == 207 {
+            Self::Asyncness::map(self.text(), |text| {
+                Err(text?
+                    .parse()
+                    .map_or_else(Error::Parsing, Error::MultiStatus))
+            })
+        } else {
+            <Self::Asyncness>::ready(Ok(self))
+        }
+    }
 }
 
 impl<T: WebClient> WebClient for super::Client<T> {
     type Asyncness = T::Asyncness;
-    type Request = T::Request;
+    type Request = ClientRequest<T::Request>;
     type Response = T::Response;
 
     fn request(&self, method: &str, url: &str) -> Self::Request {
-        let request = self.web_client.request(method, url);
-        if let Auth::Basic { username, password } = &self.authentication {
-            let auth = basic_auth(username, password.as_deref());
-            request.header(b"authorization", auth)
-        } else {
-            request
+        let url = &self.url(url);
+        let auth = &self.authentication;
+        ClientRequest {
+            request: self.web_client.request(method, url),
+            retry: auth
+                .may_retry()
+                .then(|| self.web_client.request(method, url)),
+            preflight: auth
+                .needs_challenge()
+                .then(|| self.web_client.request("HEAD", url)),
+            auth: auth.clone(),
+            method: method.to_owned(),
+            url: url.to_owned(),
+        }
+    }
+}
+
+/// Request created by [`Client`], sends the credentials of its [`Auth`] and
+/// is resent once after an authentication challenge.
+pub struct ClientRequest<R> {
+    request: R,
+    /// Identical request, resent after a `401 Unauthorized` challenge.
+    retry: Option<R>,
+    /// `HEAD` request to receive a challenge before sending a streamed body,
+    /// as those cannot be resent.
+    preflight: Option<R>,
+    auth: Auth,
+    method: String,
+    url: String,
+}
+
+impl<R: Request + Send + 'static> Request for ClientRequest<R>
+where
+    R::Response: 'static,
+{
+    type Asyncness = R::Asyncness;
+    type Response = R::Response;
+
+    fn header(self, key: &[u8], value: Vec<u8>) -> Self {
+        Self {
+            retry: self.retry.map(|retry| retry.header(key, value.clone())),
+            request: self.request.header(key, value),
+            ..self
+        }
+    }
+
+    /// Keeps a copy of `body` to resend it, if the [`Auth`] requires it.
+    fn send(
+        self,
+        body: Option<Vec<u8>>,
+    ) -> <Self::Asyncness as Asyncness>::Future<Result<Self::Response>> {
+        #![allow(deprecated)]
+        let Self {
+            request,
+            retry,
+            auth,
+            method,
+            url,
+            ..
+        } = self;
+        let request = auth.authorize(request, &method, &url);
+        let Some(retry) = retry else {
+            return request.send(body);
+        };
+        let retry_body = body.clone();
+        Self::Asyncness::flat_and_then(request.send(body), move |response| {
+            if auth.challenge(&response) {
+                auth.authorize(retry, &method, &url).send(retry_body)
+            } else {
+                Self::Asyncness::ready(Ok(response))
+            }
+        })
+    }
+
+    fn send_stream(
+        self,
+        stream: <Self::Asyncness as Asyncness>::Stream,
+        length: Option<u64>,
+    ) -> <Self::Asyncness as Asyncness>::Future<Result<Self::Response>> {
+        #![allow(deprecated)]
+        let Self {
+            request,
+            preflight,
+            auth,
+            method,
+            url,
+            ..
+        } = self;
+        let send = move |auth: Auth| {
+            let response = auth
+                .authorize(request, &method, &url)
+                .send_stream(stream, length);
+            // The body cannot be resent, but following requests can use the
+            // challenge.
+            Self::Asyncness::and_then(response, move |response| {
+                auth.challenge(&response);
+                Ok(response)
+            })
+        };
+        match preflight.filter(|_| auth.needs_challenge()) {
+            Some(preflight) => {
+                Self::Asyncness::flat_and_then(preflight.send(None), move |response| {
+                    auth.challenge(&response);
+                    send(auth)
+                })
+            }
+            None => send(auth),
         }
     }
 }
@@ -109,6 +368,7 @@ pub struct Async;
 #[cfg(feature = "async")]
 impl Asyncness for Async {
     type Future<T: 'static> = BoxFuture<T>;
+    type Stream = futures_util::stream::BoxStream<'static, Result<Vec<u8>>>;
 
     fn ready<T: Send + 'static>(value: T) -> Self::Future<T> {
         std::future::ready(value).boxed()
@@ -146,6 +406,7 @@ impl Asyncness for Async {
 pub struct Blocking;
 impl Asyncness for Blocking {
     type Future<T: 'static> = T;
+    type Stream = Box<dyn Read + Send>;
 
     fn ready<T: Send>(value: T) -> T {
         value
@@ -173,8 +434,10 @@ mod reqwest_impl {
 
     use std::str::FromStr;
 
-    use futures_util::{FutureExt, TryFutureExt};
-    use reqwest::{Client, RequestBuilder, Response};
+    use futures_util::stream::BoxStream;
+    use futures_util::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+    use reqwest::header::{CONTENT_LENGTH, HeaderValue};
+    use reqwest::{Body, Client, RequestBuilder, Response};
 
     use super::{Async, BoxFuture, Error, Result, WebClient};
 
@@ -206,6 +469,22 @@ mod reqwest_impl {
             .map_err(Error::web_request)
             .boxed()
         }
+
+        fn send_stream(
+            self,
+            stream: BoxStream<'static, Result<Vec<u8>>>,
+            length: Option<u64>,
+        ) -> BoxFuture<Result<Response>> {
+            let request = self.body(Body::wrap_stream(stream));
+            if let Some(length) = length {
+                request.header(CONTENT_LENGTH, length)
+            } else {
+                request
+            }
+            .send()
+            .map_err(Error::web_request)
+            .boxed()
+        }
     }
 
     impl super::Response for Response {
@@ -218,18 +497,39 @@ mod reqwest_impl {
                 .boxed()
         }
 
+        fn stream(self) -> BoxStream<'static, Result<Vec<u8>>> {
+            self.bytes_stream()
+                .map_ok(|b| b.to_vec())
+                .map_err(Error::web_request)
+                .boxed()
+        }
+
         fn status(&self) -> u16 {
             self.status().as_u16()
         }
+
+        fn header(&self, name: &str) -> Option<&[u8]> {
+            self.headers().get(name).map(HeaderValue::as_bytes)
+        }
+
+        fn header_all(&self, name: &str) -> Vec<&[u8]> {
+            self.headers()
+                .get_all(name)
+                .iter()
+                .map(HeaderValue::as_bytes)
+                .collect()
+        }
     }
 }
 
 #[cfg(feature = "reqwest-blocking")]
 mod reqwest_blocking_impl {
 
+    use std::io::Read;
     use std::str::FromStr;
 
-    use reqwest::blocking::{Client, RequestBuilder, Response};
+    use reqwest::blocking::{Body, Client, RequestBuilder, Response};
+    use reqwest::header::HeaderValue;
 
     use super::{Blocking, Error, Result, WebClient};
 
@@ -260,6 +560,19 @@ mod reqwest_blocking_impl {
             .send()
             .map_err(Error::web_request)
         }
+
+        fn send_stream(
+            self,
+            stream: Box<dyn Read + Send>,
+            length: Option<u64>,
+        ) -> Result<Response> {
+            self.body(match length {
+                Some(length) => Body::sized(stream, length),
+                None => Body::new(stream),
+            })
+            .send()
+            .map_err(Error::web_request)
+        }
     }
 
     impl super::Response for Response {
@@ -269,16 +582,36 @@ mod reqwest_blocking_impl {
             self.bytes().map(|b| b.to_vec()).map_err(Error::web_request)
         }
 
+        fn stream(self) -> Box<dyn Read + Send> {
+            Box::new(self)
+        }
+
         fn status(&self) -> u16 {
             self.status().as_u16()
         }
+
+        fn header(&self, name: &str) -> Option<&[u8]> {
+            self.headers().get(name).map(HeaderValue::as_bytes)
+        }
+
+        fn header_all(&self, name: &str) -> Vec<&[u8]> {
+            self.headers()
+                .get_all(name)
+                .iter()
+                .map(HeaderValue::as_bytes)
+                .collect()
+        }
     }
 }
 
 #[cfg(feature = "ureq")]
 mod ureq_impl {
+    use std::io::Read;
+
+    use http::HeaderValue;
+    use http::header::CONTENT_LENGTH;
     use http::response::Response;
-    use ureq::Body;
+    use ureq::{Body, SendBody};
 
     use super::{Blocking, Error, Request, Result, WebClient};
 
@@ -324,6 +657,29 @@ mod ureq_impl {
                     .map_err(Error::web_request)
             }
         }
+
+        fn send_stream(
+            self,
+            stream: Box<dyn Read + Send>,
+            length: Option<u64>,
+        ) -> Result<Response<Body>> {
+            let builder = if let Some(length) = length {
+                self.1.header(CONTENT_LENGTH, length)
+            } else {
+                self.1
+            };
+            let request = builder
+                .body(SendBody::from_owned_reader(stream))
+                .map_err(Error::web_request)?;
+            self.0
+                .run(
+                    self.0
+                        .configure_request(request)
+                        .allow_non_standard_methods(true)
+                        .build(),
+                )
+                .map_err(Error::web_request)
+        }
     }
 
     impl super::Response for Response<Body> {
@@ -333,62 +689,160 @@ mod ureq_impl {
             self.into_body().read_to_vec().map_err(Error::web_request)
         }
 
+        fn stream(self) -> Box<dyn Read + Send> {
+            Box::new(self.into_body().into_reader())
+        }
+
         fn status(&self) -> u16 {
             self.status().as_u16()
         }
+
+        fn header(&self, name: &str) -> Option<&[u8]> {
+            self.headers().get(name).map(HeaderValue::as_bytes)
+        }
+
+        fn header_all(&self, name: &str) -> Vec<&[u8]> {
+            self.headers()
+                .get_all(name)
+                .iter()
+                .map(HeaderValue::as_bytes)
+                .collect()
+        }
     }
 }
 
 #[cfg(feature = "minreq")]
-pub use minreq_impl::Minreq;
+pub use minreq_impl::{Minreq, MinreqRequest, MinreqResponse};
 #[cfg(feature = "minreq")]
 mod minreq_impl {
-    use minreq::{Request, Response};
+    use std::io::{self, Read};
+
+    use minreq::{Request, ResponseLazy};
 
     use super::{Blocking, Error, Result, WebClient, str};
+
+    /// [`WebClient`] using [`minreq`], response bodies are streamed.
+    ///
+    /// minreq does not support streaming request bodies, so a
+    /// [`Body::Stream`](super::Body::Stream), e.g., uploaded with
+    /// [`Client::put`](crate::Client::put), is read into memory completely
+    /// before it is sent.
+    #[derive(Debug, Clone, Copy, Default)]
     pub struct Minreq;
     impl WebClient for Minreq {
         type Asyncness = Blocking;
-        type Request = Request;
-        type Response = Response;
+        type Request = MinreqRequest;
+        type Response = MinreqResponse;
 
-        fn request(&self, method: &str, url: &str) -> Request {
-            minreq::Request::new(::minreq::Method::Custom(method.to_owned()), url)
+        fn request(&self, method: &str, url: &str) -> MinreqRequest {
+            MinreqRequest {
+                request: Request::new(::minreq::Method::Custom(method.to_owned()), url),
+                head: method.eq_ignore_ascii_case("HEAD"),
+            }
         }
     }
 
-    impl super::Request for Request {
+    /// Request of [`Minreq`].
+    pub struct MinreqRequest {
+        request: Request,
+        /// Whether this is a `HEAD` request, whose response has no body.
+        head: bool,
+    }
+
+    impl super::Request for MinreqRequest {
         type Asyncness = Blocking;
-        type Response = Response;
+        type Response = MinreqResponse;
 
         fn header(self, key: &[u8], value: Vec<u8>) -> Self {
-            self.with_header(
-                str::from_utf8(key).unwrap(),
-                String::from_utf8(value).unwrap(),
-            )
+            Self {
+                request: self.request.with_header(
+                    str::from_utf8(key).unwrap(),
+                    String::from_utf8(value).unwrap(),
+                ),
+                ..self
+            }
         }
 
-        fn send(self, body: Option<Vec<u8>>) -> Result<Response> {
-            if let Some(body) = body {
-                self.with_body(body)
+        fn send(self, body: Option<Vec<u8>>) -> Result<MinreqResponse> {
+            let Self { request, head } = self;
+            let response = if let Some(body) = body {
+                request.with_body(body)
             } else {
-                self
+                request
             }
-            .send()
-            .map_err(Error::web_request)
+            .send_lazy()
+            .map_err(Error::web_request)?;
+            // minreq would wait for a body that is never sent.
+            let has_body = !head && !matches!(response.status_code, 204 | 304);
+            Ok(MinreqResponse { response, has_body })
+        }
+
+        /// Reads the whole `stream` into memory, as minreq does not support
+        /// streaming request bodies.
+        fn send_stream(
+            self,
+            mut stream: Box<dyn Read + Send>,
+            _length: Option<u64>,
+        ) -> Result<MinreqResponse> {
+            let mut body = Vec::new();
+            stream.read_to_end(&mut body).map_err(Error::web_request)?;
+            #[allow(deprecated)]
+            self.send(Some(body))
         }
     }
 
-    impl super::Response for Response {
+    /// Response of [`Minreq`], the body is read from the connection while
+    /// [streaming](super::Response::stream) it.
+    pub struct MinreqResponse {
+        response: ResponseLazy,
+        has_body: bool,
+    }
+
+    impl Read for MinreqResponse {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut read = 0;
+            while self.has_body && read < buf.len() {
+                match self.response.next() {
+                    Some(Ok((byte, _))) => {
+                        buf[read] = byte;
+                        read += 1;
+                    }
+                    Some(Err(error)) => return Err(io::Error::other(error)),
+                    None => self.has_body = false,
+                }
+            }
+            Ok(read)
+        }
+    }
+
+    impl super::Response for MinreqResponse {
         type Asyncness = Blocking;
 
-        fn bytes(self) -> Result<Vec<u8>> {
-            Ok(self.into_bytes())
+        fn bytes(mut self) -> Result<Vec<u8>> {
+            let mut body = Vec::new();
+            self.read_to_end(&mut body).map_err(Error::web_request)?;
+            Ok(body)
+        }
+
+        fn stream(self) -> Box<dyn Read + Send> {
+            Box::new(self)
         }
 
         fn status(&self) -> u16 {
             use intentional::CastInto;
-            self.status_code.cast_into()
+            self.response.status_code.cast_into()
+        }
+
+        fn header(&self, name: &str) -> Option<&[u8]> {
+            self.response
+                .headers
+                .get(&name.to_ascii_lowercase())
+                .map(String::as_bytes)
+        }
+
+        /// minreq only keeps one value per header.
+        fn header_all(&self, name: &str) -> Vec<&[u8]> {
+            self.header(name).into_iter().collect()
         }
     }
 }
@@ -397,12 +851,15 @@ mod minreq_impl {
 pub use attohttpc_impl::Attohttpc;
 #[cfg(feature = "attohttpc")]
 mod attohttpc_impl {
-    use attohttpc::body::Bytes;
+    use std::io::{self, Read, Write};
+
+    use attohttpc::body::{Body, BodyKind, Bytes};
     use attohttpc::{RequestBuilder, Response};
-    use http::Method;
+    use http::{HeaderValue, Method};
     use intentional::Assert;
 
     /// Marker struct used until <https://github.com/sbstp/attohttpc/issues/188> is resolved.
+    #[derive(Debug, Clone, Copy, Default)]
     pub struct Attohttpc;
 
     use super::{Blocking, Error, Result, WebClient, str};
@@ -432,6 +889,34 @@ mod attohttpc_impl {
             }
             .map_err(Error::web_request)
         }
+
+        fn send_stream(
+            self,
+            stream: Box<dyn Read + Send>,
+            length: Option<u64>,
+        ) -> Result<Response> {
+            self.body(Reader { stream, length })
+                .send()
+                .map_err(Error::web_request)
+        }
+    }
+
+    /// Streamed request body.
+    ///
+    /// Cannot be rewound, so it is empty when resent after a redirect.
+    struct Reader {
+        stream: Box<dyn Read + Send>,
+        length: Option<u64>,
+    }
+
+    impl Body for Reader {
+        fn kind(&mut self) -> io::Result<BodyKind> {
+            Ok(self.length.map_or(BodyKind::Chunked, BodyKind::KnownLength))
+        }
+
+        fn write<W: Write>(&mut self, mut writer: W) -> io::Result<()> {
+            io::copy(&mut self.stream, &mut writer).map(drop)
+        }
     }
 
     impl super::Response for Response {
@@ -441,8 +926,75 @@ mod attohttpc_impl {
             self.bytes().map_err(Error::web_request)
         }
 
+        fn stream(self) -> Box<dyn Read + Send> {
+            Box::new(self.split().2)
+        }
+
         fn status(&self) -> u16 {
             self.status().as_u16()
         }
+
+        fn header(&self, name: &str) -> Option<&[u8]> {
+            self.headers().get(name).map(HeaderValue::as_bytes)
+        }
+
+        fn header_all(&self, name: &str) -> Vec<&[u8]> {
+            self.headers()
+                .get_all(name)
+                .iter()
+                .map(HeaderValue::as_bytes)
+                .collect()
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    any(feature = "ureq", feature = "reqwest", feature = "reqwest-blocking")
+))]
+mod tests {
+    use super::*;
+
+    /// Response with a repeated `WWW-Authenticate` header.
+    fn response<T>(body: T) -> http::Response<T> {
+        http::Response::builder()
+            .header("ETag", r#""e1""#)
+            .header("WWW-Authenticate", "Bearer")
+            .header("www-authenticate", r#"Basic realm="dav""#)
+            .header("X-Binary", &b"\xff"[..])
+            .body(body)
+            .unwrap()
+    }
+
+    fn check_headers(response: &impl Response) {
+        assert_eq!(response.header("etag"), Some(&br#""e1""#[..]));
+        assert_eq!(response.header_str("ETAG"), Some(r#""e1""#));
+        assert_eq!(response.header("www-authenticate"), Some(&b"Bearer"[..]));
+        assert_eq!(response.header_all("WWW-Authenticate"), [
+            &b"Bearer"[..],
+            br#"Basic realm="dav""#
+        ]);
+        assert_eq!(response.header("x-binary"), Some(&b"\xff"[..]));
+        assert_eq!(response.header_str("x-binary"), None);
+        assert_eq!(response.header("missing"), None);
+        assert!(response.header_all("missing").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "ureq")]
+    fn ureq_headers() {
+        check_headers(&response(ureq::Body::builder().data("")));
+    }
+
+    #[test]
+    #[cfg(feature = "reqwest")]
+    fn reqwest_headers() {
+        check_headers(&reqwest::Response::from(response("")));
+    }
+
+    #[test]
+    #[cfg(feature = "reqwest-blocking")]
+    fn reqwest_blocking_headers() {
+        check_headers(&reqwest::blocking::Response::from(response("")));
     }
 }
@@ -2,45 +2,494 @@
 //! Types to parse WebDAV responses.
 use core::fmt;
 use std::collections::HashMap;
+use std::num::ParseIntError;
+use std::str::{self, FromStr};
 
-use crowd::visit;
-use derive_more::{IntoIterator, TryUnwrap};
-use intentional::Assert;
-use serde::Deserialize;
+use derive_more::{Display, Error, IntoIterator, TryUnwrap};
+use quick_xml::DeError;
+use quick_xml::encoding::EncodingError;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::ResolveResult;
+use quick_xml::reader::NsReader;
+use time::OffsetDateTime;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
 
-#[derive(Debug, Clone, Deserialize, IntoIterator)]
+use crate::Depth;
+
+/// Commonly used namespace URIs.
+pub mod namespace {
+    /// WebDAV namespace, usually bound to the prefix `d`.
+    pub const DAV: &str = "DAV:";
+    /// ownCloud namespace, usually bound to the prefix `oc`.
+    pub const OWNCLOUD: &str = "http://owncloud.org/ns";
+    /// Nextcloud namespace, usually bound to the prefix `nc`.
+    pub const NEXTCLOUD: &str = "http://nextcloud.org/ns";
+    /// CalDAV namespace ([RFC 4791](https://www.rfc-editor.org/rfc/rfc4791)).
+    pub const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+    /// CardDAV namespace ([RFC 6352](https://www.rfc-editor.org/rfc/rfc6352)).
+    pub const CARDDAV: &str = "urn:ietf:params:xml:ns:carddav";
+    /// Calendar Server namespace, e.g., for `getctag`, usually bound to the
+    /// prefix `cs`.
+    pub const CALENDARSERVER: &str = "http://calendarserver.org/ns/";
+    /// Apple iCal namespace, e.g., for `calendar-color`.
+    pub const APPLE_ICAL: &str = "http://apple.com/ns/ical/";
+}
+
+/// Name of an XML element consisting of namespace URI and local name.
+///
+/// Displayed in Clark notation, i.e., `{namespace}name`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Display)]
+#[display("{{{namespace}}}{name}")]
+pub struct QualifiedName {
+    /// Namespace URI, empty for elements without namespace.
+    pub namespace: String,
+    /// Local name without prefix.
+    pub name: String,
+}
+
+impl QualifiedName {
+    #[must_use]
+    pub fn new(namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            name: name.into(),
+        }
+    }
+
+    /// Name in the [`DAV:`](namespace::DAV) namespace.
+    #[must_use]
+    pub fn dav(name: impl Into<String>) -> Self {
+        Self::new(namespace::DAV, name)
+    }
+
+    #[must_use]
+    pub fn is(&self, namespace: &str, name: &str) -> bool {
+        self.namespace == namespace && self.name == name
+    }
+}
+
+impl<N: Into<String>, L: Into<String>> From<(N, L)> for QualifiedName {
+    fn from((namespace, name): (N, L)) -> Self {
+        Self::new(namespace, name)
+    }
+}
+
+impl FromStr for QualifiedName {
+    type Err = DeError;
+
+    /// Parses Clark notation (`{namespace}name`), names without braces have
+    /// no namespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(s) = s.strip_prefix('{') {
+            let (namespace, name) = s
+                .split_once('}')
+                .ok_or_else(|| DeError::Custom(format!("expected closing `}}` in `{{{s}`")))?;
+            Ok(Self::new(namespace, name))
+        } else {
+            Ok(Self::new("", s))
+        }
+    }
+}
+
+#[derive(Debug, Clone, IntoIterator)]
 pub struct MultiStatus {
-    #[serde(rename = "response")]
+    #[into_iterator(owned)]
     pub responses: Vec<Response>,
+    /// `DAV:responsedescription`, human-readable description.
+    pub response_description: Option<String>,
+}
+
+impl FromStr for MultiStatus {
+    type Err = DeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let root = Element::parse(s)?;
+        if !root.name.is(namespace::DAV, "multistatus") {
+            return Err(DeError::Custom(format!(
+                "expected `{{DAV:}}multistatus` got `{}`",
+                root.name
+            )));
+        }
+        let mut responses = Vec::new();
+        let mut response_description = None;
+        for child in root.into_elements() {
+            if child.name.is(namespace::DAV, "response") {
+                responses.push(Response::from_element(child)?);
+            } else if child.name.is(namespace::DAV, "responsedescription") {
+                response_description = Some(child.into_text());
+            }
+        }
+        Ok(Self {
+            responses,
+            response_description,
+        })
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default)]
 pub struct Response {
     pub href: String,
+    /// Empty if the response has a [`status`](Self::status) instead.
     pub propstat: Vec<PropStat>,
+    /// Status of the whole resource, used instead of `propstat`, e.g., for
+    /// errors of `DELETE` or `COPY` or resources not found by `PROPFIND`.
+    pub status: Option<Status>,
+    /// `DAV:error`, precondition or postcondition that failed.
+    pub error: Option<PropValue>,
+    /// `DAV:responsedescription`, human-readable description.
+    pub response_description: Option<String>,
+}
+
+impl Response {
+    /// Whether [`status`](Self::status) is missing or successful.
+    ///
+    /// Note that the statuses of the individual [`propstat`](Self::propstat)
+    /// entries are not considered.
+    #[must_use]
+    pub fn is_successful(&self) -> bool {
+        self.status.as_ref().is_none_or(Status::is_successful)
+    }
+
+    /// Decoded path of [`href`](Self::href) relative to `request_url`, the
+    /// URL the request was sent to, without leading or trailing `/`.
+    ///
+    /// Returns an empty string for the requested resource itself and `None`
+    /// if `href` is outside of `request_url`.
+    ///
+    /// ```
+    /// # use webdav_client::webdav_types::Response;
+    /// let response = Response {
+    ///     href: "/dav/files/user/Documents/Read%20me.md".into(),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     response
+    ///         .relative_path("https://example.com/dav/files/user/")
+    ///         .as_deref(),
+    ///     Some("Documents/Read me.md")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn relative_path(&self, request_url: &str) -> Option<String> {
+        crate::url::relative_path(request_url, &self.href)
+    }
+
+    /// Returns the value of property `{namespace}name` from the first
+    /// successful [`PropStat`] containing it.
+    #[must_use]
+    pub fn get(&self, namespace: &str, name: &str) -> Option<&PropValue> {
+        self.propstat
+            .iter()
+            .filter(|propstat| propstat.status.is_successful())
+            .find_map(|propstat| propstat.get(namespace, name))
+    }
+
+    /// Parses all [`DavProperties`] contained in this response.
+    pub fn dav_properties(&self) -> Result<DavProperties, PropertyError> {
+        Ok(DavProperties {
+            etag: self.etag()?,
+            last_modified: self.last_modified()?,
+            creation_date: self.creation_date()?,
+            content_length: self.content_length()?,
+            content_type: self.content_type()?,
+            resource_type: self.resource_type()?,
+            display_name: self.display_name()?,
+            lock_discovery: self.lock_discovery()?,
+            supported_lock: self.supported_lock()?,
+        })
+    }
+
+    /// `DAV:getetag`
+    pub fn etag(&self) -> Result<Option<String>, PropertyError> {
+        self.dav_text("getetag")
+    }
+
+    /// `DAV:getlastmodified`
+    pub fn last_modified(&self) -> Result<Option<OffsetDateTime>, PropertyError> {
+        self.dav_parsed("getlastmodified", parse_date)
+    }
+
+    /// `DAV:creationdate`
+    pub fn creation_date(&self) -> Result<Option<OffsetDateTime>, PropertyError> {
+        self.dav_parsed("creationdate", parse_date)
+    }
+
+    /// `DAV:getcontentlength`
+    pub fn content_length(&self) -> Result<Option<u64>, PropertyError> {
+        self.dav_parsed("getcontentlength", |value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|e: ParseIntError| e.to_string())
+        })
+    }
+
+    /// `DAV:getcontenttype`
+    pub fn content_type(&self) -> Result<Option<String>, PropertyError> {
+        self.dav_text("getcontenttype")
+    }
+
+    /// `DAV:resourcetype`, empty for non-collection resources.
+    pub fn resource_type(&self) -> Result<Option<Vec<ResourceType>>, PropertyError> {
+        let Some(value) = self.get(namespace::DAV, "resourcetype") else {
+            return Ok(None);
+        };
+        Ok(Some(match value {
+            PropValue::Empty => Vec::new(),
+            PropValue::Text(text) => {
+                return Err(PropertyError::new(
+                    "resourcetype",
+                    text,
+                    "expected elements",
+                ));
+            }
+            PropValue::Xml(types) => types.keys().cloned().map(ResourceType::from).collect(),
+        }))
+    }
+
+    /// `DAV:displayname`
+    pub fn display_name(&self) -> Result<Option<String>, PropertyError> {
+        self.dav_text("displayname")
+    }
+
+    /// `DAV:lockdiscovery`, the currently active locks.
+    pub fn lock_discovery(&self) -> Result<Option<Vec<Lock>>, PropertyError> {
+        self.get(namespace::DAV, "lockdiscovery")
+            .map(Lock::from_lock_discovery)
+            .transpose()
+    }
+
+    /// `DAV:supportedlock`, the lock capabilities of the resource.
+    pub fn supported_lock(&self) -> Result<Option<Vec<LockScope>>, PropertyError> {
+        self.get(namespace::DAV, "supportedlock")
+            .map(|value| {
+                value
+                    .get(namespace::DAV, "lockentry")
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|entry| {
+                        LockScope::from_prop_value(
+                            entry
+                                .get(namespace::DAV, "lockscope")
+                                .and_then(<[_]>::first)
+                                .ok_or_else(|| {
+                                    PropertyError::new("lockentry", "", "missing scope")
+                                })?,
+                        )
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    fn dav_text(&self, name: &str) -> Result<Option<String>, PropertyError> {
+        self.dav_parsed(name, |value| Ok(value.to_owned()))
+    }
+
+    fn dav_parsed<T>(
+        &self,
+        name: &str,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Result<Option<T>, PropertyError> {
+        match self.get(namespace::DAV, name) {
+            None | Some(PropValue::Empty) => Ok(None),
+            Some(PropValue::Text(value)) => parse(value.trim())
+                .map(Some)
+                .map_err(|reason| PropertyError::new(name, value, reason)),
+            Some(PropValue::Xml(_)) => Err(PropertyError::new(name, "", "expected text")),
+        }
+    }
+
+    fn from_element(element: Element) -> Result<Self, DeError> {
+        let mut href = None;
+        let mut propstat = Vec::new();
+        let mut status = None;
+        let mut error = None;
+        let mut response_description = None;
+        for child in element.into_elements() {
+            if child.name.namespace != namespace::DAV {
+                continue;
+            }
+            match child.name.name.as_str() {
+                "href" if href.is_none() => href = Some(child.into_text()),
+                "propstat" => propstat.push(PropStat::from_element(child)?),
+                "status" => status = Some(child.into_text().parse()?),
+                "error" => error = Some(PropValue::from_nodes(child.children)),
+                "responsedescription" => response_description = Some(child.into_text()),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            href: href.ok_or_else(|| missing("href"))?,
+            propstat,
+            status,
+            error,
+            response_description,
+        })
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone)]
 pub struct PropStat {
     pub status: Status,
-    pub prop: HashMap<String, PropValue>,
+    pub prop: HashMap<QualifiedName, PropValue>,
+    /// `DAV:error`, precondition or postcondition that failed.
+    pub error: Option<PropValue>,
+    /// `DAV:responsedescription`, human-readable description.
+    pub response_description: Option<String>,
+}
+
+impl PropStat {
+    /// Returns the value of property `{namespace}name`.
+    #[must_use]
+    pub fn get(&self, namespace: &str, name: &str) -> Option<&PropValue> {
+        self.prop.get(&QualifiedName::new(namespace, name))
+    }
+
+    fn from_element(element: Element) -> Result<Self, DeError> {
+        let mut status = None;
+        let mut prop = HashMap::new();
+        let mut error = None;
+        let mut response_description = None;
+        for child in element.into_elements() {
+            if child.name.namespace != namespace::DAV {
+                continue;
+            }
+            match child.name.name.as_str() {
+                "status" => status = Some(child.into_text().parse()?),
+                "prop" => prop.extend(
+                    child
+                        .into_elements()
+                        .map(|e| (e.name, PropValue::from_nodes(e.children))),
+                ),
+                "error" => error = Some(PropValue::from_nodes(child.children)),
+                "responsedescription" => response_description = Some(child.into_text()),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            status: status.ok_or_else(|| missing("status"))?,
+            prop,
+            error,
+            response_description,
+        })
+    }
+}
+
+/// HTTP status line, e.g., `HTTP/1.1 404 Not Found`.
+#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[display("{version} {code} {reason}")]
+pub struct Status {
+    /// HTTP version, e.g., `HTTP/1.1`.
+    pub version: String,
+    /// Numeric status code.
+    pub code: u16,
+    /// Reason phrase, can be empty.
+    pub reason: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct Status(pub String);
 impl Status {
+    /// Whether the status code is `2xx`.
     #[must_use]
     pub fn is_successful(&self) -> bool {
-        self.0.contains(" 2")
+        (200..300).contains(&self.code)
+    }
+}
+
+impl FromStr for Status {
+    type Err = DeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DeError::Custom(format!("invalid status line `{s}`"));
+        let (version, rest) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let (code, reason) = rest.split_once(' ').unwrap_or((rest, ""));
+        if !version.starts_with("HTTP/") || code.len() != 3 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            version: version.to_owned(),
+            code: code.parse().map_err(|_| invalid())?,
+            reason: reason.to_owned(),
+        })
     }
 }
 
+/// Value of a property or one of its child elements.
+///
+/// [`Text`](Self::Text) is kept as sent, including surrounding whitespace,
+/// while whitespace-only text is ignored.
+///
+/// Attributes of child elements are contained as children named `@name`
+/// without namespace, see [`attribute`](Self::attribute).
 #[derive(Clone, TryUnwrap)]
 #[try_unwrap(ref)]
 pub enum PropValue {
     Empty,
     Text(String),
-    Xml(HashMap<String, Vec<PropValue>>),
+    Xml(HashMap<QualifiedName, Vec<PropValue>>),
+}
+
+impl PropValue {
+    /// Returns the values of the child elements `{namespace}name`.
+    #[must_use]
+    pub fn get(&self, namespace: &str, name: &str) -> Option<&[PropValue]> {
+        let PropValue::Xml(children) = self else {
+            return None;
+        };
+        children
+            .get(&QualifiedName::new(namespace, name))
+            .map(Vec::as_slice)
+    }
+
+    /// Returns the value of the attribute `name` of this element, e.g.,
+    /// `VEVENT` for `<comp name="VEVENT"/>`.
+    ///
+    /// Attributes are only available for elements without text content.
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        match self.get("", &format!("@{name}"))?.first()? {
+            PropValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn from_nodes(nodes: Vec<Node>) -> Self {
+        let mut text = None::<String>;
+        let mut children = HashMap::<_, Vec<_>>::new();
+        for node in nodes {
+            match node {
+                Node::Text(t) => text.get_or_insert_default().push_str(&t),
+                Node::Element(element) => children
+                    .entry(element.name.clone())
+                    .or_default()
+                    .push(PropValue::from_element(element)),
+            }
+        }
+        if let Some(text) = text.filter(|text| !text.trim().is_empty()) {
+            PropValue::Text(text)
+        } else if children.is_empty() {
+            PropValue::Empty
+        } else {
+            PropValue::Xml(children)
+        }
+    }
+
+    fn from_element(element: Element) -> Self {
+        let value = PropValue::from_nodes(element.children);
+        if element.attributes.is_empty() || matches!(value, PropValue::Text(_)) {
+            return value;
+        }
+        let mut children = match value {
+            PropValue::Xml(children) => children,
+            _ => HashMap::new(),
+        };
+        for (name, value) in element.attributes {
+            children.insert(QualifiedName::new("", format!("@{name}")), vec![
+                PropValue::Text(value),
+            ]);
+        }
+        PropValue::Xml(children)
+    }
 }
 
 impl fmt::Debug for PropValue {
@@ -53,29 +502,632 @@ impl fmt::Debug for PropValue {
     }
 }
 
-impl<'de> Deserialize<'de> for PropValue {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        deserializer.deserialize_any(visit! {
-            PropValue, "anything",
-            str(s) {
-                Ok(PropValue::Text(s.to_owned()))
+/// Error returned when a property value is malformed.
+#[derive(Debug, Clone, Display, Error)]
+#[display("invalid value {value:?} for `{name}`: {reason}")]
+pub struct PropertyError {
+    /// Property name.
+    pub name: QualifiedName,
+    /// Raw value of the property.
+    pub value: String,
+    /// Description of what is wrong.
+    pub reason: String,
+}
+
+impl PropertyError {
+    fn new(name: &str, value: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            name: QualifiedName::dav(name),
+            value: value.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Live properties defined by [RFC 4918](https://www.rfc-editor.org/rfc/rfc4918#section-15),
+/// see [`Response::dav_properties`].
+///
+/// Properties are `None` if they were not requested or not returned by the
+/// server.
+#[derive(Debug, Clone, Default)]
+pub struct DavProperties {
+    /// `DAV:getetag`
+    pub etag: Option<String>,
+    /// `DAV:getlastmodified`
+    pub last_modified: Option<OffsetDateTime>,
+    /// `DAV:creationdate`
+    pub creation_date: Option<OffsetDateTime>,
+    /// `DAV:getcontentlength`
+    pub content_length: Option<u64>,
+    /// `DAV:getcontenttype`
+    pub content_type: Option<String>,
+    /// `DAV:resourcetype`, empty for non-collection resources.
+    pub resource_type: Option<Vec<ResourceType>>,
+    /// `DAV:displayname`
+    pub display_name: Option<String>,
+    /// `DAV:lockdiscovery`
+    pub lock_discovery: Option<Vec<Lock>>,
+    /// `DAV:supportedlock`
+    pub supported_lock: Option<Vec<LockScope>>,
+}
+
+impl DavProperties {
+    /// Whether the resource is a collection (directory).
+    #[must_use]
+    pub fn is_collection(&self) -> bool {
+        self.resource_type
+            .as_ref()
+            .is_some_and(|types| types.contains(&ResourceType::Collection))
+    }
+}
+
+/// Single entry of `DAV:resourcetype`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceType {
+    /// `DAV:collection`
+    Collection,
+    /// `DAV:principal`
+    Principal,
+    /// CalDAV `calendar`
+    Calendar,
+    /// CardDAV `addressbook`
+    Addressbook,
+    Other(QualifiedName),
+}
+
+impl From<QualifiedName> for ResourceType {
+    fn from(name: QualifiedName) -> Self {
+        match (name.namespace.as_str(), name.name.as_str()) {
+            (namespace::DAV, "collection") => Self::Collection,
+            (namespace::DAV, "principal") => Self::Principal,
+            (namespace::CALDAV, "calendar") => Self::Calendar,
+            (namespace::CARDDAV, "addressbook") => Self::Addressbook,
+            _ => Self::Other(name),
+        }
+    }
+}
+
+/// `DAV:lockscope`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockScope {
+    Exclusive,
+    Shared,
+}
+
+impl LockScope {
+    fn from_prop_value(value: &PropValue) -> Result<Self, PropertyError> {
+        if value.get(namespace::DAV, "exclusive").is_some() {
+            Ok(Self::Exclusive)
+        } else if value.get(namespace::DAV, "shared").is_some() {
+            Ok(Self::Shared)
+        } else {
+            Err(PropertyError::new(
+                "lockscope",
+                format!("{value:?}"),
+                "expected `exclusive` or `shared`",
+            ))
+        }
+    }
+}
+
+/// `DAV:timeout`, displayed in the format of the `Timeout` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Display)]
+pub enum LockTimeout {
+    #[display("Second-{_0}")]
+    Seconds(u64),
+    Infinite,
+}
+
+impl FromStr for LockTimeout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("infinite") {
+            Ok(Self::Infinite)
+        } else if let Some(seconds) = s.strip_prefix("Second-") {
+            seconds
+                .parse()
+                .map(Self::Seconds)
+                .map_err(|e: ParseIntError| e.to_string())
+        } else {
+            Err("expected `Infinite` or `Second-n`".to_owned())
+        }
+    }
+}
+
+/// Active lock on a resource (`DAV:activelock`).
+#[derive(Debug, Clone)]
+pub struct Lock {
+    pub scope: LockScope,
+    pub depth: Depth,
+    /// Owner information provided when creating the lock.
+    pub owner: Option<PropValue>,
+    pub timeout: Option<LockTimeout>,
+    /// Lock token, usually an `opaquelocktoken:` or `urn:uuid:` URI.
+    pub token: Option<String>,
+    /// URL the lock was created on.
+    pub root: Option<String>,
+}
+
+impl Lock {
+    fn from_prop_value(value: &PropValue) -> Result<Self, PropertyError> {
+        let text = |name: &str| match value.get(namespace::DAV, name)?.first()? {
+            PropValue::Text(text) => Some(text.trim()),
+            _ => None,
+        };
+        let href = |name: &str| match value
+            .get(namespace::DAV, name)?
+            .first()?
+            .get(namespace::DAV, "href")?
+            .first()?
+        {
+            PropValue::Text(text) => Some(text.trim().to_owned()),
+            _ => None,
+        };
+        Ok(Self {
+            scope: LockScope::from_prop_value(
+                value
+                    .get(namespace::DAV, "lockscope")
+                    .and_then(<[_]>::first)
+                    .ok_or_else(|| PropertyError::new("activelock", "", "missing lockscope"))?,
+            )?,
+            depth: match text("depth") {
+                Some(depth) if depth.eq_ignore_ascii_case("infinity") => Depth::Infinity,
+                Some(depth) => Depth::Some(depth.parse().map_err(|e: ParseIntError| {
+                    PropertyError::new("depth", depth, e.to_string())
+                })?),
+                None => return Err(PropertyError::new("activelock", "", "missing depth")),
             },
-            map(mut a) {
-                let mut map = HashMap::<_, Vec<_>>::new();
-                while let Some((key, value)) = a.next_entry::<String, PropValue>()? {
-                    map.entry(key).or_default().push(value);
-                }
-                Ok(if map.is_empty() {
-                    PropValue::Empty
-                } else if let Some(mut text) = map.remove("$text"){
-                    text.pop().assert("only inserts vecs with at least length 1")
-                } else {
-                    PropValue::Xml(map)
+            owner: value
+                .get(namespace::DAV, "owner")
+                .and_then(<[_]>::first)
+                .cloned(),
+            timeout: text("timeout")
+                .map(|timeout| {
+                    timeout
+                        .parse()
+                        .map_err(|reason| PropertyError::new("timeout", timeout, reason))
                 })
+                .transpose()?,
+            token: href("locktoken"),
+            root: href("lockroot"),
+        })
+    }
+
+    fn from_lock_discovery(value: &PropValue) -> Result<Vec<Self>, PropertyError> {
+        value
+            .get(namespace::DAV, "activelock")
+            .unwrap_or_default()
+            .iter()
+            .map(Lock::from_prop_value)
+            .collect()
+    }
+}
+
+/// Body of a `LOCK` response, i.e., the `DAV:lockdiscovery` of the locked
+/// resource.
+#[derive(Debug, Clone, IntoIterator)]
+pub struct LockDiscovery {
+    #[into_iterator(owned)]
+    pub locks: Vec<Lock>,
+}
+
+impl FromStr for LockDiscovery {
+    type Err = DeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let root = Element::parse(s)?;
+        if !root.name.is(namespace::DAV, "prop") {
+            return Err(DeError::Custom(format!(
+                "expected `{{DAV:}}prop` got `{}`",
+                root.name
+            )));
+        }
+        let lock_discovery = root
+            .into_elements()
+            .find(|child| child.name.is(namespace::DAV, "lockdiscovery"))
+            .ok_or_else(|| missing("lockdiscovery"))?;
+        Ok(Self {
+            locks: Lock::from_lock_discovery(&PropValue::from_nodes(lock_discovery.children))
+                .map_err(|e| DeError::Custom(e.to_string()))?,
+        })
+    }
+}
+
+/// Parses `DAV:getlastmodified` (RFC 1123) and `DAV:creationdate` (RFC 3339)
+/// values, accepting both formats for either.
+fn parse_date(value: &str) -> Result<OffsetDateTime, String> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .or_else(|_| OffsetDateTime::parse(value, &Rfc2822))
+        .map_err(|e| e.to_string())
+}
+
+fn missing(name: &str) -> DeError {
+    DeError::Custom(format!("missing field `{name}`"))
+}
+
+/// Namespace aware XML element, intermediate representation for parsing.
+struct Element {
+    name: QualifiedName,
+    /// Local names and values of the attributes, without namespace
+    /// declarations.
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+enum Node {
+    Element(Element),
+    Text(String),
+}
+
+impl Element {
+    fn parse(xml: &str) -> Result<Self, DeError> {
+        fn utf8(bytes: &[u8]) -> Result<&str, DeError> {
+            Ok(str::from_utf8(bytes).map_err(EncodingError::from)?)
+        }
+
+        fn attributes(start: &BytesStart) -> Result<Vec<(String, String)>, DeError> {
+            let mut attributes = Vec::new();
+            for attribute in start.attributes() {
+                let attribute = attribute.map_err(quick_xml::Error::from)?;
+                if attribute.key.as_namespace_binding().is_none() {
+                    attributes.push((
+                        utf8(attribute.key.local_name().as_ref())?.to_owned(),
+                        attribute.unescape_value()?.into_owned(),
+                    ));
+                }
             }
+            Ok(attributes)
+        }
+
+        // Text is kept as is, e.g., for the trailing line break of
+        // iCalendar data, whitespace-only text is dropped in `from_nodes`.
+        let mut reader = NsReader::from_str(xml);
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            let (namespace, event) = reader.read_resolved_event()?;
+            let namespace = match namespace {
+                ResolveResult::Bound(namespace) => utf8(namespace.as_ref())?.to_owned(),
+                ResolveResult::Unbound => String::new(),
+                ResolveResult::Unknown(prefix) => {
+                    return Err(DeError::Custom(format!(
+                        "unknown namespace prefix `{}`",
+                        String::from_utf8_lossy(&prefix)
+                    )));
+                }
+            };
+            let finished = match event {
+                Event::Start(start) => {
+                    stack.push(Element {
+                        name: QualifiedName::new(namespace, utf8(start.local_name().as_ref())?),
+                        attributes: attributes(&start)?,
+                        children: Vec::new(),
+                    });
+                    continue;
+                }
+                Event::Empty(start) => Element {
+                    name: QualifiedName::new(namespace, utf8(start.local_name().as_ref())?),
+                    attributes: attributes(&start)?,
+                    children: Vec::new(),
+                },
+                Event::End(_) => stack.pop().ok_or(DeError::UnexpectedEof)?,
+                Event::Text(text) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent
+                            .children
+                            .push(Node::Text(text.unescape()?.into_owned()));
+                    }
+                    continue;
+                }
+                Event::CData(data) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(Node::Text(utf8(&data)?.to_owned()));
+                    }
+                    continue;
+                }
+                Event::Eof => return Err(DeError::UnexpectedEof),
+                _ => continue,
+            };
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(Node::Element(finished));
+            } else {
+                return Ok(finished);
+            }
+        }
+    }
+
+    fn into_elements(self) -> impl Iterator<Item = Element> {
+        self.children.into_iter().filter_map(|node| match node {
+            Node::Element(element) => Some(element),
+            Node::Text(_) => None,
         })
     }
+
+    fn into_text(self) -> String {
+        let text: String = self
+            .children
+            .into_iter()
+            .filter_map(|node| match node {
+                Node::Text(text) => Some(text),
+                Node::Element(_) => None,
+            })
+            .collect();
+        text.trim().to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multi_status(responses: &str) -> Result<MultiStatus, DeError> {
+        format!(r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:">{responses}</d:multistatus>"#)
+            .parse()
+    }
+
+    /// Response with a single successful propstat containing `prop`.
+    fn response(prop: &str) -> Response {
+        multi_status(&format!(
+            r"<d:response>
+                 <d:href>/file</d:href>
+                 <d:propstat>
+                   <d:prop>{prop}</d:prop>
+                   <d:status>HTTP/1.1 200 OK</d:status>
+                 </d:propstat>
+               </d:response>"
+        ))
+        .unwrap()
+        .responses
+        .remove(0)
+    }
+
+    #[test]
+    fn last_modified() {
+        let valid =
+            response("<d:getlastmodified>Sat, 01 Mar 2025 10:00:00 GMT</d:getlastmodified>");
+        assert_eq!(
+            valid.last_modified().unwrap().unwrap(),
+            OffsetDateTime::from_unix_timestamp(1_740_823_200).unwrap()
+        );
+        assert!(response("").last_modified().unwrap().is_none());
+        assert!(
+            response("<d:getlastmodified/>")
+                .last_modified()
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            response("<d:getlastmodified>yesterday</d:getlastmodified>")
+                .last_modified()
+                .is_err()
+        );
+        assert!(
+            response("<d:getlastmodified><d:a/></d:getlastmodified>")
+                .last_modified()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn creation_date() {
+        let valid = response("<d:creationdate>2025-03-01T11:00:00+01:00</d:creationdate>");
+        assert_eq!(
+            valid.creation_date().unwrap().unwrap(),
+            OffsetDateTime::from_unix_timestamp(1_740_823_200).unwrap()
+        );
+        assert!(
+            response("<d:creationdate/>")
+                .creation_date()
+                .unwrap()
+                .is_none()
+        );
+        let error = response("<d:creationdate>2025-13-01</d:creationdate>")
+            .creation_date()
+            .unwrap_err();
+        assert_eq!(error.name, QualifiedName::dav("creationdate"));
+        assert_eq!(error.value, "2025-13-01");
+    }
+
+    #[test]
+    fn content_length() {
+        let valid = response("<d:getcontentlength> 42 </d:getcontentlength>");
+        assert_eq!(valid.content_length().unwrap(), Some(42));
+        assert!(
+            response("<d:getcontentlength/>")
+                .content_length()
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            response("<d:getcontentlength>-1</d:getcontentlength>")
+                .content_length()
+                .is_err()
+        );
+        assert!(
+            response("<d:getcontentlength>big</d:getcontentlength>")
+                .content_length()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn resource_type() {
+        let valid = response(
+            r#"<d:resourcetype xmlns:c="urn:ietf:params:xml:ns:caldav"><d:collection/><c:calendar/></d:resourcetype>"#,
+        );
+        let mut types = valid.resource_type().unwrap().unwrap();
+        types.sort_by_key(|r#type| format!("{type:?}"));
+        assert_eq!(types, [ResourceType::Calendar, ResourceType::Collection]);
+        assert!(valid.dav_properties().unwrap().is_collection());
+        // Empty for non-collection resources, `None` if missing.
+        assert_eq!(
+            response("<d:resourcetype/>").resource_type().unwrap(),
+            Some(Vec::new())
+        );
+        assert_eq!(response("").resource_type().unwrap(), None);
+        assert!(
+            response("<d:resourcetype>collection</d:resourcetype>")
+                .resource_type()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn lock_discovery() {
+        let valid = response(
+            "<d:lockdiscovery>
+              <d:activelock>
+                <d:locktype><d:write/></d:locktype>
+                <d:lockscope><d:exclusive/></d:lockscope>
+                <d:depth>infinity</d:depth>
+                <d:owner><d:href>alice</d:href></d:owner>
+                <d:timeout>Second-3600</d:timeout>
+                <d:locktoken><d:href>urn:uuid:1</d:href></d:locktoken>
+                <d:lockroot><d:href>/file</d:href></d:lockroot>
+              </d:activelock>
+            </d:lockdiscovery>",
+        );
+        let locks = valid.lock_discovery().unwrap().unwrap();
+        assert_eq!(locks.len(), 1);
+        let lock = &locks[0];
+        assert_eq!(lock.scope, LockScope::Exclusive);
+        assert_eq!(lock.depth, Depth::Infinity);
+        assert_eq!(lock.timeout, Some(LockTimeout::Seconds(3600)));
+        assert_eq!(lock.token.as_deref(), Some("urn:uuid:1"));
+        assert_eq!(lock.root.as_deref(), Some("/file"));
+        assert!(lock.owner.is_some());
+
+        assert_eq!(
+            response("<d:lockdiscovery/>")
+                .lock_discovery()
+                .unwrap()
+                .unwrap()
+                .len(),
+            0
+        );
+        assert!(response("").lock_discovery().unwrap().is_none());
+        for malformed in [
+            // Missing depth.
+            "<d:activelock><d:lockscope><d:shared/></d:lockscope></d:activelock>",
+            r"<d:activelock>
+                <d:lockscope><d:shared/></d:lockscope>
+                <d:depth>1</d:depth>
+                <d:timeout>forever</d:timeout>
+              </d:activelock>",
+            r"<d:activelock>
+                <d:lockscope><d:private/></d:lockscope>
+                <d:depth>0</d:depth>
+              </d:activelock>",
+        ] {
+            let response = response(&format!("<d:lockdiscovery>{malformed}</d:lockdiscovery>"));
+            assert!(response.lock_discovery().is_err(), "{malformed}");
+            assert!(response.dav_properties().is_err());
+        }
+    }
+
+    #[test]
+    fn namespaces() {
+        let multi_status: MultiStatus = r#"<?xml version="1.0"?>
+            <multistatus xmlns="DAV:" xmlns:a="http://example.com/a">
+              <response>
+                <href>/file</href>
+                <propstat>
+                  <prop>
+                    <getetag>"1"</getetag>
+                    <a:color>red</a:color>
+                    <color xmlns="http://example.com/b">blue</color>
+                    <a:owner xmlns:a="http://example.com/c"><a:name>alice</a:name></a:owner>
+                    <plain xmlns=""><nested>text</nested></plain>
+                  </prop>
+                  <status>HTTP/1.1 200 OK</status>
+                </propstat>
+              </response>
+            </multistatus>"#
+            .parse()
+            .unwrap();
+        let response = &multi_status.responses[0];
+        // Default namespace is inherited.
+        assert_eq!(response.etag().unwrap().as_deref(), Some("\"1\""));
+        // Same local name in different namespaces.
+        let text = |namespace, name| match response.get(namespace, name) {
+            Some(PropValue::Text(text)) => Some(text.as_str()),
+            _ => None,
+        };
+        assert_eq!(text("http://example.com/a", "color"), Some("red"));
+        assert_eq!(text("http://example.com/b", "color"), Some("blue"));
+        assert_eq!(text(namespace::DAV, "color"), None);
+        // Prefix rebound on a nested element applies to its children.
+        let owner = response.get("http://example.com/c", "owner").unwrap();
+        assert!(owner.get("http://example.com/c", "name").is_some());
+        assert!(response.get("http://example.com/a", "owner").is_none());
+        // `xmlns=""` removes the default namespace.
+        let plain = response.get("", "plain").unwrap();
+        assert!(matches!(plain.get("", "nested"), Some([PropValue::Text(text)]) if text == "text"));
+        assert!(response.get(namespace::DAV, "plain").is_none());
+    }
+
+    #[test]
+    fn unknown_prefix() {
+        assert!(multi_status("<x:response/>").is_err());
+        assert!("<multistatus/>".parse::<MultiStatus>().is_err());
+    }
+
+    #[test]
+    fn status_only_response() {
+        let multi_status = multi_status(
+            r"<d:response>
+                <d:href>/missing</d:href>
+                <d:status>HTTP/1.1 404 Not Found</d:status>
+              </d:response>",
+        )
+        .unwrap();
+        let response = &multi_status.responses[0];
+        assert_eq!(response.href, "/missing");
+        assert!(response.propstat.is_empty());
+        assert_eq!(
+            response.status,
+            Some(Status {
+                version: "HTTP/1.1".into(),
+                code: 404,
+                reason: "Not Found".into(),
+            })
+        );
+        assert!(!response.is_successful());
+    }
+
+    #[test]
+    fn status_line() {
+        let status: Status = " HTTP/1.1 404 2 things\n".parse().unwrap();
+        assert_eq!(status.code, 404);
+        assert_eq!(status.reason, "2 things");
+        assert_eq!(status.to_string(), "HTTP/1.1 404 2 things");
+
+        let status: Status = "HTTP/2 204".parse().unwrap();
+        assert_eq!((status.code, status.reason.as_str()), (204, ""));
+
+        for invalid in [
+            "",
+            "HTTP/1.1",
+            "garbage 200 OK",
+            "HTTP/1.1 2000 OK",
+            "HTTP/1.1 OK 200",
+        ] {
+            assert!(invalid.parse::<Status>().is_err(), "{invalid:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_status() {
+        for response in [
+            "<d:response><d:href>/</d:href><d:status>garbage</d:status></d:response>",
+            "<d:response><d:href>/</d:href><d:status/></d:response>",
+            "<d:response><d:href>/</d:href><d:propstat><d:prop/></d:propstat></d:response>",
+            r"<d:response>
+                <d:href>/</d:href>
+                <d:propstat><d:prop/><d:status>HTTP/1.1 abc</d:status></d:propstat>
+              </d:response>",
+        ] {
+            assert!(multi_status(response).is_err(), "{response}");
+        }
+    }
 }